@@ -1,6 +1,7 @@
 use std::{f64::consts, ptr};
 
 use oxphys_numerics::{
+    enums::lane_width::LaneWidth,
     functions::{constant, sqrt, variable},
     traits::expression::Expression,
 };
@@ -40,7 +41,7 @@ fn main() {
     let variables_len = variables.len();
     let start = std::time::Instant::now();
     for _ in 0..num_iterations {
-        let mut result = f(variables_ptr, variables_len);
+        let mut result = f.call(variables_ptr, variables_len);
         unsafe {
             ptr::write_volatile(&mut result, result);
         }
@@ -49,11 +50,41 @@ fn main() {
     let duration = start.elapsed();
     println!("Duration: {:?}", duration);
 
+    // Now jit compile a SIMD-lane batch kernel, evaluating `lanes` points per call instead of
+    // one, so the per-call overhead that dominates the scalar `compile_nd` benchmark above is
+    // amortized across more work. Every lane gets the same (x, y) here, since the benchmark only
+    // has one sample point, but the packed layout is exactly what a real batch of distinct points
+    // would look like.
+    let lane_width = LaneWidth::Four;
+    let lanes = lane_width.lanes();
+    let f_vec = expr.compile_nd_vec(lane_width).unwrap();
+    let mut packed_variables = vec![0.0; variables_len * lanes];
+    for (variable_index, value) in variables.iter().enumerate() {
+        for lane in 0..lanes {
+            packed_variables[variable_index * lanes + lane] = *value;
+        }
+    }
+    let mut packed_output = vec![0.0; lanes];
+    let start = std::time::Instant::now();
+    for _ in 0..(num_iterations / lanes) {
+        f_vec.call(
+            packed_variables.as_ptr(),
+            packed_output.as_mut_ptr(),
+            variables_len,
+        );
+        unsafe {
+            ptr::write_volatile(&mut packed_output[0], packed_output[0]);
+        }
+    }
+
+    let duration = start.elapsed();
+    println!("Duration: {:?}", duration);
+
     // Now jit compiler to specifically a 2d function.
     let f = expr.compile_2d().unwrap();
     let start = std::time::Instant::now();
     for _ in 0..num_iterations {
-        let mut result = f(variables[0], variables[1]);
+        let mut result = f.call(variables[0], variables[1]);
         unsafe {
             ptr::write_volatile(&mut result, result);
         }
@@ -0,0 +1,156 @@
+//! # oxphys_numerics REPL
+//!
+//! A small interactive front-end over [`oxphys_numerics::parse`]: read an expression string,
+//! resolve its free variables, JIT-compile it, and prompt for each variable's value before
+//! printing the result. Lines are buffered and re-parsed as the user types, so an expression can
+//! be split across several lines whenever parentheses or a trailing operator leave it obviously
+//! incomplete.
+
+use std::io::{self, BufRead, Write};
+
+use oxphys_numerics::enums::expr::Expr;
+use oxphys_numerics::enums::initialized_expr::InitializedExpr;
+use oxphys_numerics::parse::parse;
+
+fn main() {
+    println!("oxphys_numerics REPL - enter an expression (e.g. `sin(x)^2 + exp(-y)`), Ctrl-D to exit.");
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut variables: Vec<String> = Vec::new();
+
+    loop {
+        print!("> ");
+        flush();
+
+        let Some(source) = read_statement(&mut lines) else {
+            break;
+        };
+
+        if source.trim().is_empty() {
+            continue;
+        }
+
+        let parsed = match parse(&source) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                println!("{err}");
+                continue;
+            }
+        };
+
+        // `initialize` grows `variables` in place, so a name reused across statements keeps the
+        // same index and previously-entered statements stay valid if we ever replayed them.
+        let initialized = match parsed.initialize(&mut variables).to_initialized() {
+            Some(initialized) => initialized,
+            None => {
+                println!("internal error: initialize() returned an uninitialized expression");
+                continue;
+            }
+        };
+
+        let Some(values) = read_variable_values(&mut lines, &variables) else {
+            break;
+        };
+
+        match evaluate(&initialized, &values) {
+            Ok(result) => println!("= {result}"),
+            Err(err) => println!("{err}"),
+        }
+    }
+}
+
+/// # Needs continuation
+/// True if `buffer` looks syntactically incomplete: more `(` than `)`, or it ends in a binary
+/// operator/comma that's still expecting a right-hand side. The REPL keeps buffering lines while
+/// this holds, only handing the accumulated text to [`parse`] once it doesn't.
+fn needs_continuation(buffer: &str) -> bool {
+    let open = buffer.matches('(').count();
+    let close = buffer.matches(')').count();
+    if open > close {
+        return true;
+    }
+
+    buffer
+        .trim_end()
+        .chars()
+        .last()
+        .is_some_and(|c| matches!(c, '+' | '-' | '*' | '/' | '^' | ',' | '='))
+}
+
+/// # Read statement
+/// Read and concatenate lines from `lines` until [`needs_continuation`] says the buffered text is
+/// complete, printing a `..` continuation prompt for every line after the first. Returns `None` on
+/// EOF (e.g. Ctrl-D), discarding any partial statement still buffered.
+fn read_statement(lines: &mut impl Iterator<Item = io::Result<String>>) -> Option<String> {
+    let mut buffer = String::new();
+
+    loop {
+        let line = lines.next()?.ok()?;
+        if !buffer.is_empty() {
+            buffer.push(' ');
+        }
+        buffer.push_str(line.trim());
+
+        if !needs_continuation(&buffer) {
+            return Some(buffer);
+        }
+
+        print!(".. ");
+        flush();
+    }
+}
+
+/// # Read variable values
+/// Prompt for a value for every name in `variables`, in index order, returning `None` on EOF.
+fn read_variable_values(
+    lines: &mut impl Iterator<Item = io::Result<String>>,
+    variables: &[String],
+) -> Option<Vec<f64>> {
+    variables
+        .iter()
+        .map(|name| loop {
+            print!("  {name} = ");
+            flush();
+
+            let line = lines.next()?.ok()?;
+            match line.trim().parse::<f64>() {
+                Ok(value) => return Some(value),
+                Err(_) => println!("not a number, try again"),
+            }
+        })
+        .collect()
+}
+
+fn flush() {
+    io::stdout().flush().ok();
+}
+
+#[cfg(feature = "jit")]
+fn evaluate(expr: &InitializedExpr, values: &[f64]) -> Result<f64, String> {
+    use oxphys_numerics::traits::expression::Expression;
+
+    let compiled = expr.compile_nd().map_err(|err| err.to_string())?;
+    Ok(compiled.call(values.as_ptr(), values.len()))
+}
+
+#[cfg(not(feature = "jit"))]
+fn evaluate(_expr: &InitializedExpr, _values: &[f64]) -> Result<f64, String> {
+    Err("evaluating an expression needs the `jit` feature; rebuild with --features jit".to_string())
+}
+
+/// A small convenience used only by this REPL: most callers pattern-match `Expr` directly, but
+/// here it's more readable to fall straight through to an `InitializedExpr` once we know
+/// `initialize` just produced one.
+trait ToInitialized {
+    fn to_initialized(self) -> Option<InitializedExpr>;
+}
+
+impl ToInitialized for Expr {
+    fn to_initialized(self) -> Option<InitializedExpr> {
+        match self {
+            Expr::Initialized(initialized) => Some(initialized),
+            Expr::Uninitialized(_) => None,
+        }
+    }
+}
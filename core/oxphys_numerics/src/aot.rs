@@ -0,0 +1,91 @@
+//! # AOT module
+//!
+//! Ahead-of-time compilation, as an alternative to the in-memory JIT exposed by
+//! `Expression::compile_nd`. Where the JIT hands back a function pointer that's only valid for
+//! the lifetime of the current process, [`Expr::compile_object`] emits a relocatable object file
+//! a caller can link into a standalone shared library and `dlopen` later, from another process or
+//! another language entirely. Gated behind the `jit` feature, like the in-memory JIT it's an
+//! alternative to: both lower through the same `ExpressionCompiler::build_jit_*` codegen.
+
+#![cfg(feature = "jit")]
+
+use std::path::Path;
+use std::process::Command;
+
+use cranelift_codegen::ir::{types, InstBuilder};
+
+use crate::enums::expr::Expr;
+use crate::enums::scalar_width::ScalarWidth;
+use crate::errors::expr_parsing_error::ExprParsingError;
+use crate::structs::instruction_set_architecture::InstructionSetArchitecture;
+use crate::structs::object_helper::ObjectHelper;
+use crate::traits::expression_compiler::ExpressionCompiler;
+
+impl Expr {
+    /// # Compile object
+    /// Ahead-of-time compile `self` to a relocatable object file, exporting it as an
+    /// `extern "C" fn(*const f64, usize) -> f64` named `symbol_name`. The returned bytes are a
+    /// standard `.o`; pass them to [`link_shared_library`] to produce a `dlopen`-able artifact.
+    pub fn compile_object(&self, symbol_name: &str) -> Result<Vec<u8>, ExprParsingError> {
+        let Expr::Initialized(initialized) = self else {
+            return Err(ExprParsingError::new_syntax(
+                "cannot compile an uninitialized expression; initialize it first".to_string(),
+            ));
+        };
+
+        let isa = InstructionSetArchitecture::current_platform();
+        let parameters = vec![isa.pointer_type(), types::I64];
+        let return_type = types::F64;
+        let mut object_helper = ObjectHelper::new(isa, symbol_name, parameters, return_type);
+        let libm = object_helper.libm_imports();
+
+        {
+            let mut builder = object_helper.function_builder();
+
+            let entry_block = builder.create_block();
+            builder.append_block_params_for_function_params(entry_block);
+            builder.switch_to_block(entry_block);
+            builder.seal_block(entry_block);
+
+            let params_slice = builder.block_params(entry_block);
+            let parameters = params_slice.to_vec();
+
+            let return_value =
+                initialized.build_jit_nd(&mut builder, &parameters, &libm, ScalarWidth::F64);
+            builder.ins().return_(&[return_value]);
+            builder.finalize();
+        }
+
+        Ok(object_helper.finalize())
+    }
+}
+
+/// # Link shared library
+/// Link a relocatable object previously produced by [`Expr::compile_object`] into a shared
+/// library at `output_path`, by shelling out to the system's C compiler driver. This keeps us
+/// from having to vendor a linker: `cc`/`clang`/`gcc` already know how to find the platform
+/// linker and produce a `cdylib`-compatible `.so`/`.dylib`/`.dll`.
+pub fn link_shared_library(object_bytes: &[u8], output_path: &Path) -> Result<(), ExprParsingError> {
+    let object_path = output_path.with_extension("o");
+    std::fs::write(&object_path, object_bytes)
+        .map_err(|error| ExprParsingError::new_syntax(error.to_string()))?;
+
+    let status = Command::new("cc")
+        .arg("-shared")
+        .arg("-o")
+        .arg(output_path)
+        .arg(&object_path)
+        .arg("-lm")
+        .status()
+        .map_err(|error| ExprParsingError::new_syntax(error.to_string()))?;
+
+    if !status.success() {
+        return Err(ExprParsingError::new_syntax(format!(
+            "linking {} into a shared library failed with {}",
+            object_path.display(),
+            status
+        )));
+    }
+
+    Ok(())
+}
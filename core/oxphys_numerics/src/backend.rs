@@ -0,0 +1,294 @@
+//! # Backend module
+//!
+//! `ExpressionCompiler` is hard-wired to `cranelift_frontend::FunctionBuilder`. This module
+//! abstracts code generation behind a [`Backend`] trait so an expression tree can be lowered by
+//! more than one code generator: [`CraneliftBackend`] (the default, used everywhere else in this
+//! crate) for fast JIT startup, and an LLVM-based [`LlvmBackend`] (behind the `llvm` feature, via
+//! `inkwell`) for maximally-optimized long-running kernels. Gated behind the `jit` feature: both
+//! backends compile down to a native function, which isn't meaningful without JIT support.
+
+#![cfg(feature = "jit")]
+
+use cranelift_codegen::ir::types;
+
+use crate::enums::{
+    initialized_expr::InitializedExpr, opt_level::OptLevel, scalar_width::ScalarWidth,
+};
+use crate::errors::expr_parsing_error::ExprParsingError;
+use crate::structs::instruction_set_architecture::InstructionSetArchitecture;
+use crate::structs::jit_helper::JITHelper;
+use crate::traits::expression::{CompiledExpressionND, CompiledFunction};
+use crate::traits::expression_compiler::ExpressionCompiler;
+
+/// # Backend
+/// A pluggable code generator capable of lowering an [`InitializedExpr`] to a callable native
+/// function. Implementations are free to pick their own IR/codegen stack; all that's required is
+/// that the end result matches the `CompiledExpressionND` calling convention.
+pub trait Backend {
+    /// # Compile nd
+    /// Compile `expr` down to a callable `fn(*const f64, usize) -> f64`, at the given
+    /// optimization level.
+    fn compile_nd(
+        &self,
+        expr: &InitializedExpr,
+        opt_level: OptLevel,
+    ) -> Result<CompiledExpressionND, ExprParsingError>;
+}
+
+/// # CraneliftBackend
+/// The default backend, used everywhere else in this crate. Favors fast JIT startup over the
+/// most aggressive possible codegen.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CraneliftBackend;
+
+impl Backend for CraneliftBackend {
+    fn compile_nd(
+        &self,
+        expr: &InitializedExpr,
+        opt_level: OptLevel,
+    ) -> Result<CompiledExpressionND, ExprParsingError> {
+        let isa = InstructionSetArchitecture::with_opt_level(opt_level);
+        let parameters = vec![isa.pointer_type(), types::I64];
+        let return_type = types::F64;
+        let mut jit_helper = JITHelper::new(isa, parameters, return_type);
+        let libm = jit_helper.libm_imports();
+
+        {
+            let mut builder = jit_helper.function_builder();
+
+            let entry_block = builder.create_block();
+            builder.append_block_params_for_function_params(entry_block);
+            builder.switch_to_block(entry_block);
+            builder.seal_block(entry_block);
+
+            let params_slice = builder.block_params(entry_block);
+            let parameters = params_slice.to_vec();
+
+            let return_value =
+                expr.build_jit_nd(&mut builder, &parameters, &libm, ScalarWidth::F64);
+            builder.ins().return_(&[return_value]);
+            builder.finalize();
+        }
+
+        let (module, code) = jit_helper.finalize();
+        let function = unsafe { std::mem::transmute::<_, fn(*const f64, usize) -> f64>(code) };
+        Ok(CompiledFunction::new(module, function))
+    }
+}
+
+#[cfg(feature = "llvm")]
+pub use llvm::LlvmBackend;
+
+#[cfg(feature = "llvm")]
+mod llvm {
+    use inkwell::context::Context;
+    use inkwell::execution_engine::ExecutionEngine;
+    use inkwell::module::Module;
+    use inkwell::passes::{PassManager, PassManagerBuilder};
+    use inkwell::values::FloatValue;
+    use inkwell::OptimizationLevel;
+
+    use crate::enums::{
+        binary_node::BinaryNode, expr::Expr, initialized_expr::InitializedExpr,
+        initialized_leaf::InitializedLeaf, opt_level::OptLevel, unary_node::UnaryNode,
+    };
+    use crate::errors::expr_parsing_error::ExprParsingError;
+    use crate::traits::expression::{CompiledExpressionND, CompiledFunction};
+
+    use super::Backend;
+
+    /// # LlvmBackend
+    /// An alternative backend that lowers an expression to LLVM IR via `inkwell`, runs standard
+    /// function passes (instcombine, gvn, reassociate), and JIT-compiles the result. Prefer this
+    /// over [`super::CraneliftBackend`] for expressions that will be called many, many times,
+    /// where LLVM's more aggressive optimizer pays for its slower compile time.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct LlvmBackend;
+
+    impl LlvmBackend {
+        /// # Build
+        /// Recursively lower an `Expr` to an LLVM `FloatValue`, loading variables from the raw
+        /// `args_ptr` function argument.
+        fn build<'ctx>(
+            expr: &Expr,
+            context: &'ctx Context,
+            builder: &inkwell::builder::Builder<'ctx>,
+            args_ptr: inkwell::values::PointerValue<'ctx>,
+        ) -> FloatValue<'ctx> {
+            match expr {
+                Expr::Uninitialized(_) => {
+                    panic!("cannot lower an uninitialized expression to LLVM IR")
+                }
+                Expr::Initialized(InitializedExpr::Leaf(InitializedLeaf::Constant(value))) => {
+                    context.f64_type().const_float(*value)
+                }
+                Expr::Initialized(InitializedExpr::Leaf(InitializedLeaf::Variable(variable))) => {
+                    let f64_type = context.f64_type();
+                    let index = variable.index() as u64;
+                    let offset = context.i64_type().const_int(index, false);
+                    let element_ptr = unsafe {
+                        builder
+                            .build_in_bounds_gep(f64_type, args_ptr, &[offset], "var_ptr")
+                            .unwrap()
+                    };
+                    builder
+                        .build_load(f64_type, element_ptr, "var")
+                        .unwrap()
+                        .into_float_value()
+                }
+                Expr::Initialized(InitializedExpr::Unary(unary)) => {
+                    Self::build_unary(unary, context, builder, args_ptr)
+                }
+                Expr::Initialized(InitializedExpr::Binary(binary)) => {
+                    Self::build_binary(binary, context, builder, args_ptr)
+                }
+                Expr::Initialized(InitializedExpr::Comparison(_)) => {
+                    unimplemented!("comparisons aren't wired into the LLVM backend yet")
+                }
+                Expr::Initialized(InitializedExpr::Ternary(_)) => {
+                    unimplemented!("select isn't wired into the LLVM backend yet")
+                }
+                Expr::Initialized(InitializedExpr::Let { .. }) => {
+                    unimplemented!("let-bindings aren't wired into the LLVM backend yet")
+                }
+            }
+        }
+
+        fn build_unary<'ctx>(
+            unary: &UnaryNode,
+            context: &'ctx Context,
+            builder: &inkwell::builder::Builder<'ctx>,
+            args_ptr: inkwell::values::PointerValue<'ctx>,
+        ) -> FloatValue<'ctx> {
+            let (inner, op): (&Expr, &str) = match unary {
+                UnaryNode::Negate(inner) => (inner, "negate"),
+                UnaryNode::Sqrt(inner) => (inner, "sqrt"),
+                UnaryNode::Sin(inner) => (inner, "sin"),
+                UnaryNode::Cos(inner) => (inner, "cos"),
+                UnaryNode::Exp(inner) => (inner, "exp"),
+                UnaryNode::Ln(inner) => (inner, "ln"),
+            };
+
+            let value = Self::build(inner, context, builder, args_ptr);
+            match op {
+                "negate" => builder.build_float_neg(value, "negate").unwrap(),
+                "sqrt" => {
+                    let intrinsic = inkwell::intrinsics::Intrinsic::find("llvm.sqrt").unwrap();
+                    let function = intrinsic
+                        .get_declaration(
+                            &builder.get_insert_block().unwrap().get_parent().unwrap().get_parent(),
+                            &[context.f64_type().into()],
+                        )
+                        .unwrap();
+                    builder
+                        .build_call(function, &[value.into()], "sqrt")
+                        .unwrap()
+                        .try_as_basic_value()
+                        .left()
+                        .unwrap()
+                        .into_float_value()
+                }
+                _ => unimplemented!("transcendental libcalls aren't wired into the LLVM backend yet"),
+            }
+        }
+
+        fn build_binary<'ctx>(
+            binary: &BinaryNode,
+            context: &'ctx Context,
+            builder: &inkwell::builder::Builder<'ctx>,
+            args_ptr: inkwell::values::PointerValue<'ctx>,
+        ) -> FloatValue<'ctx> {
+            let (left, right, op): (&Expr, &Expr, &str) = match binary {
+                BinaryNode::Add(left, right) => (left, right, "add"),
+                BinaryNode::Subtract(left, right) => (left, right, "sub"),
+                BinaryNode::Multiply(left, right) => (left, right, "mul"),
+                BinaryNode::Frac(left, right) => (left, right, "div"),
+                BinaryNode::Pow(_, _) | BinaryNode::Log(_, _) => {
+                    unimplemented!("Pow/Log libm calls aren't wired into the LLVM backend yet")
+                }
+            };
+
+            let left_value = Self::build(left, context, builder, args_ptr);
+            let right_value = Self::build(right, context, builder, args_ptr);
+            match op {
+                "add" => builder.build_float_add(left_value, right_value, "add").unwrap(),
+                "sub" => builder.build_float_sub(left_value, right_value, "sub").unwrap(),
+                "mul" => builder.build_float_mul(left_value, right_value, "mul").unwrap(),
+                "div" => builder.build_float_div(left_value, right_value, "div").unwrap(),
+                _ => unreachable!(),
+            }
+        }
+
+        /// # Run passes
+        /// Run the standard function passes appropriate for `opt_level` over `module`, mirroring
+        /// the optimization pipeline used by the LLVM codegen path in compilers like Roc.
+        fn run_passes(module: &Module, opt_level: OptLevel) {
+            let pass_manager_builder = PassManagerBuilder::create();
+            pass_manager_builder.set_optimization_level(match opt_level {
+                OptLevel::None => OptimizationLevel::None,
+                OptLevel::Speed => OptimizationLevel::Aggressive,
+                OptLevel::SpeedAndSize => OptimizationLevel::Default,
+            });
+
+            let function_pass_manager = PassManager::create(module);
+            pass_manager_builder.populate_function_pass_manager(&function_pass_manager);
+            function_pass_manager.add_instruction_combining_pass();
+            function_pass_manager.add_gvn_pass();
+            function_pass_manager.add_reassociate_pass();
+
+            for function in module.get_functions() {
+                function_pass_manager.run_on(&function);
+            }
+        }
+    }
+
+    impl Backend for LlvmBackend {
+        fn compile_nd(
+            &self,
+            expr: &InitializedExpr,
+            opt_level: OptLevel,
+        ) -> Result<CompiledExpressionND, ExprParsingError> {
+            // Heap-allocate the context so its address is stable: the execution engine below
+            // ends up borrowing from it, and that borrow needs to outlive this function.
+            let context = Box::new(Context::create());
+            let module = context.create_module("oxphys_numerics");
+            let builder = context.create_builder();
+
+            let f64_type = context.f64_type();
+            let ptr_type = context.ptr_type(inkwell::AddressSpace::default());
+            let i64_type = context.i64_type();
+            let fn_type = f64_type.fn_type(&[ptr_type.into(), i64_type.into()], false);
+
+            let function = module.add_function("f", fn_type, None);
+            let entry_block = context.append_basic_block(function, "entry");
+            builder.position_at_end(entry_block);
+
+            let args_ptr = function.get_nth_param(0).unwrap().into_pointer_value();
+            let root = Expr::Initialized(expr.clone());
+            let return_value = Self::build(&root, &context, &builder, args_ptr);
+            builder.build_return(Some(&return_value)).unwrap();
+
+            Self::run_passes(&module, opt_level);
+
+            let execution_engine = module
+                .create_jit_execution_engine(OptimizationLevel::Aggressive)
+                .map_err(|error| ExprParsingError::new_syntax(error.to_string()))?;
+
+            let function = unsafe {
+                let raw = execution_engine
+                    .get_function_address("f")
+                    .map_err(|error| ExprParsingError::new_syntax(error.to_string()))?;
+                std::mem::transmute::<usize, fn(*const f64, usize) -> f64>(raw)
+            };
+
+            // Keep the execution engine (and the context it borrows from) alive for as long as
+            // the returned function might be called, instead of leaking them. The engine borrows
+            // from `context`, so extending its lifetime to `'static` is sound as long as the two
+            // are dropped together in borrow order - they're tupled with the engine first so that
+            // holds, and `context`'s heap allocation means its address never moves underneath it.
+            let execution_engine: ExecutionEngine<'static> =
+                unsafe { std::mem::transmute(execution_engine) };
+            Ok(CompiledFunction::new((execution_engine, context), function))
+        }
+    }
+}
@@ -0,0 +1,721 @@
+//! # DAG module
+//!
+//! Hash-consing support for `Expr` trees. A freshly-built expression tree can contain many
+//! structurally-identical subexpressions (e.g. `(x+y)*(x+y)`), and naively walking it with
+//! `ExpressionCompiler::build_jit_*` emits a fresh Cranelift instruction for every occurrence.
+//! This module turns a tree into a DAG of structurally-distinct nodes addressed by a stable
+//! [`NodeId`], so a single walk can compute each unique subexpression's `Value` exactly once and
+//! reuse it everywhere else it appears. Gated behind the `jit` feature: every entry point here
+//! (`compile_nd_cse`/`compile_gradient_nd`) JIT-compiles the result, so there's nothing useful
+//! to expose when `jit` is off. `hash_cons` returns a `Result` rather than a bare `NodeId` because
+//! let-bindings can't be hash-consed yet: interning is a flat post-order pass over the whole tree,
+//! but a let's scope needs its value declared as a Cranelift variable before its body is walked,
+//! and nothing here threads that declaration through the node order. `compile_nd_cse`/
+//! `compile_gradient_nd` surface that as an `ExprParsingError` instead of hash-consing
+//! incorrectly or panicking.
+
+#![cfg(feature = "jit")]
+
+use std::collections::HashMap;
+
+use cranelift_codegen::ir::condcodes::FloatCC;
+use cranelift_codegen::ir::{types, InstBuilder, MemFlags, Value};
+use cranelift_frontend::FunctionBuilder;
+
+use crate::enums::{
+    binary_node::{integer_power, BinaryNode},
+    comparison_node::ComparisonNode,
+    expr::Expr,
+    initialized_expr::InitializedExpr,
+    initialized_leaf::InitializedLeaf,
+    scalar_width::ScalarWidth,
+    ternary_node::TernaryNode,
+    unary_node::UnaryNode,
+};
+use crate::errors::expr_parsing_error::ExprParsingError;
+use crate::structs::instruction_set_architecture::InstructionSetArchitecture;
+use crate::structs::jit_helper::JITHelper;
+use crate::structs::libm_imports::LibmImports;
+use crate::structs::transcendental_kernels;
+use crate::traits::expression::{CompiledExpressionND, CompiledFunction, CompiledGradientND};
+use crate::traits::expression_node::ExpressionNode;
+
+/// # NodeId
+/// A stable identifier for a structurally-distinct node within an [`ExprDag`]. Children always
+/// have a smaller `NodeId` than their parents, because nodes are interned in post-order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// # NodeKey
+/// The canonical, hashable representation of a node, used to intern structurally-identical
+/// subtrees into the same [`NodeId`]. `Pow`, `Frac`, `Subtract`, `Log`, and the comparisons are not
+/// commutative, so operand order is always preserved in the key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NodeKey {
+    /// The raw bits of an `f64` constant, so that distinct bit patterns (including different
+    /// NaN payloads) are never conflated by hash-consing.
+    Constant(u64),
+    Variable(usize),
+    Negate(NodeId),
+    Sqrt(NodeId),
+    Sin(NodeId),
+    Cos(NodeId),
+    Exp(NodeId),
+    Ln(NodeId),
+    Add(NodeId, NodeId),
+    Subtract(NodeId, NodeId),
+    Multiply(NodeId, NodeId),
+    Frac(NodeId, NodeId),
+    Pow(NodeId, NodeId),
+    Log(NodeId, NodeId),
+    Less(NodeId, NodeId),
+    LessEq(NodeId, NodeId),
+    Greater(NodeId, NodeId),
+    Equal(NodeId, NodeId),
+    Select(NodeId, NodeId, NodeId),
+}
+
+/// # ExprDag
+/// A hash-consed DAG built from an `Expr` tree: every structurally-distinct subexpression
+/// appears exactly once, addressed by its [`NodeId`]. Because nodes are interned bottom-up, the
+/// `nodes` vector is already in a valid build order: a node's children always appear earlier in
+/// the vector than the node itself.
+pub struct ExprDag {
+    nodes: Vec<NodeKey>,
+    interned: HashMap<NodeKey, NodeId>,
+}
+
+impl ExprDag {
+    fn new() -> Self {
+        ExprDag {
+            nodes: Vec::new(),
+            interned: HashMap::new(),
+        }
+    }
+
+    /// # Intern
+    /// Look up `key` in the interning table, inserting a fresh `NodeId` the first time this
+    /// exact structural shape is seen and returning the existing one on every subsequent hit.
+    fn intern(&mut self, key: NodeKey) -> NodeId {
+        if let Some(id) = self.interned.get(&key) {
+            return *id;
+        }
+
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(key.clone());
+        self.interned.insert(key, id);
+        id
+    }
+
+    /// # Get
+    /// Get the `NodeKey` for a given `NodeId`.
+    pub fn get(&self, id: NodeId) -> &NodeKey {
+        &self.nodes[id.0]
+    }
+
+    /// # Len
+    /// The number of structurally-distinct nodes in the DAG.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// # Is empty
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// # Hash cons
+/// Walk an `Expr` tree in post-order, interning every subexpression into `dag` and returning the
+/// `NodeId` of the root. Only initialized expressions can be hash-consed, since CSE is a
+/// pre-JIT pass and uninitialized variables don't yet have stable indices to hash on.
+pub fn hash_cons(expr: &Expr, dag: &mut ExprDag) -> Result<NodeId, ExprParsingError> {
+    match expr {
+        Expr::Initialized(initialized) => hash_cons_initialized(initialized, dag),
+        Expr::Uninitialized(_) => {
+            panic!("cannot hash-cons an uninitialized expression; initialize it first")
+        }
+    }
+}
+
+fn hash_cons_initialized(
+    expr: &InitializedExpr,
+    dag: &mut ExprDag,
+) -> Result<NodeId, ExprParsingError> {
+    match expr {
+        InitializedExpr::Leaf(InitializedLeaf::Constant(value)) => {
+            Ok(dag.intern(NodeKey::Constant(value.to_bits())))
+        }
+        InitializedExpr::Leaf(InitializedLeaf::Variable(variable)) => {
+            Ok(dag.intern(NodeKey::Variable(variable.index())))
+        }
+        InitializedExpr::Leaf(InitializedLeaf::Bound(_)) => Err(ExprParsingError::new_syntax(
+            "cannot hash-cons a let-bound name on its own; it must appear inside the body of \
+             the `InitializedExpr::Let` that binds it, which is itself rejected by CSE"
+                .to_string(),
+        )),
+        InitializedExpr::Unary(unary) => hash_cons_unary(unary, dag),
+        InitializedExpr::Binary(binary) => hash_cons_binary(binary, dag),
+        InitializedExpr::Comparison(comparison) => hash_cons_comparison(comparison, dag),
+        InitializedExpr::Ternary(ternary) => hash_cons_ternary(ternary, dag),
+        InitializedExpr::Let { .. } => Err(ExprParsingError::new_syntax(
+            "let-bindings are not yet supported by compile_nd_cse/compile_gradient_nd: \
+             hash-consing interns nodes in a flat post-order pass, but a let's scope needs its \
+             value declared as a Cranelift variable before its body is walked, which that pass \
+             can't express yet. Use Expression::compile_nd/compile_nd_batch instead, which walk \
+             the tree directly and already support let-bindings."
+                .to_string(),
+        )),
+    }
+}
+
+fn hash_cons_unary(unary: &UnaryNode, dag: &mut ExprDag) -> Result<NodeId, ExprParsingError> {
+    let (inner, wrap): (&Expr, fn(NodeId) -> NodeKey) = match unary {
+        UnaryNode::Negate(inner) => (inner, NodeKey::Negate),
+        UnaryNode::Sqrt(inner) => (inner, NodeKey::Sqrt),
+        UnaryNode::Sin(inner) => (inner, NodeKey::Sin),
+        UnaryNode::Cos(inner) => (inner, NodeKey::Cos),
+        UnaryNode::Exp(inner) => (inner, NodeKey::Exp),
+        UnaryNode::Ln(inner) => (inner, NodeKey::Ln),
+    };
+
+    let inner_id = hash_cons(inner, dag)?;
+    Ok(dag.intern(wrap(inner_id)))
+}
+
+fn hash_cons_binary(binary: &BinaryNode, dag: &mut ExprDag) -> Result<NodeId, ExprParsingError> {
+    let (left, right, wrap): (&Expr, &Expr, fn(NodeId, NodeId) -> NodeKey) = match binary {
+        BinaryNode::Add(left, right) => (left, right, NodeKey::Add),
+        BinaryNode::Subtract(left, right) => (left, right, NodeKey::Subtract),
+        BinaryNode::Multiply(left, right) => (left, right, NodeKey::Multiply),
+        BinaryNode::Frac(left, right) => (left, right, NodeKey::Frac),
+        BinaryNode::Pow(left, right) => (left, right, NodeKey::Pow),
+        BinaryNode::Log(left, right) => (left, right, NodeKey::Log),
+    };
+
+    let left_id = hash_cons(left, dag)?;
+    let right_id = hash_cons(right, dag)?;
+    Ok(dag.intern(wrap(left_id, right_id)))
+}
+
+fn hash_cons_comparison(
+    comparison: &ComparisonNode,
+    dag: &mut ExprDag,
+) -> Result<NodeId, ExprParsingError> {
+    let (left, right, wrap): (&Expr, &Expr, fn(NodeId, NodeId) -> NodeKey) = match comparison {
+        ComparisonNode::Less(left, right) => (left, right, NodeKey::Less),
+        ComparisonNode::LessEq(left, right) => (left, right, NodeKey::LessEq),
+        ComparisonNode::Greater(left, right) => (left, right, NodeKey::Greater),
+        ComparisonNode::Equal(left, right) => (left, right, NodeKey::Equal),
+    };
+
+    let left_id = hash_cons(left, dag)?;
+    let right_id = hash_cons(right, dag)?;
+    Ok(dag.intern(wrap(left_id, right_id)))
+}
+
+fn hash_cons_ternary(ternary: &TernaryNode, dag: &mut ExprDag) -> Result<NodeId, ExprParsingError> {
+    match ternary {
+        TernaryNode::Select(cond, if_true, if_false) => {
+            let cond_id = hash_cons(cond, dag)?;
+            let if_true_id = hash_cons(if_true, dag)?;
+            let if_false_id = hash_cons(if_false, dag)?;
+            Ok(dag.intern(NodeKey::Select(cond_id, if_true_id, if_false_id)))
+        }
+    }
+}
+
+impl ExprDag {
+    /// # Build jit nd
+    /// Lower the whole DAG to Cranelift IR in a single forward pass, memoizing each node's
+    /// `Value` by `NodeId` so a shared subexpression is only ever emitted once, however many
+    /// times it's referenced.
+    fn build_jit_nd(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+    ) -> Vec<Value> {
+        let args_ptr = parameters[0];
+        let mut values: Vec<Value> = Vec::with_capacity(self.nodes.len());
+
+        for key in &self.nodes {
+            let value = match key {
+                NodeKey::Constant(bits) => builder.ins().f64const(f64::from_bits(*bits)),
+                NodeKey::Variable(index) => {
+                    let offset = (*index * 8) as i32;
+                    builder
+                        .ins()
+                        .load(types::F64, MemFlags::new(), args_ptr, offset)
+                }
+                NodeKey::Negate(inner) => builder.ins().fneg(values[inner.0]),
+                NodeKey::Sqrt(inner) => builder.ins().sqrt(values[inner.0]),
+                NodeKey::Sin(inner) => transcendental_kernels::build_sin(builder, values[inner.0]),
+                NodeKey::Cos(inner) => transcendental_kernels::build_cos(builder, values[inner.0]),
+                NodeKey::Exp(inner) => transcendental_kernels::build_exp(builder, values[inner.0]),
+                NodeKey::Ln(inner) => transcendental_kernels::build_ln(builder, values[inner.0]),
+                NodeKey::Add(left, right) => builder.ins().fadd(values[left.0], values[right.0]),
+                NodeKey::Subtract(left, right) => {
+                    builder.ins().fsub(values[left.0], values[right.0])
+                }
+                NodeKey::Multiply(left, right) => {
+                    builder.ins().fmul(values[left.0], values[right.0])
+                }
+                NodeKey::Frac(left, right) => builder.ins().fdiv(values[left.0], values[right.0]),
+                NodeKey::Pow(base, exponent) => match self.nodes[exponent.0] {
+                    NodeKey::Constant(bits) => {
+                        let exponent_value = f64::from_bits(bits);
+                        if exponent_value.fract() == 0.0 && exponent_value.abs() <= 64.0 {
+                            integer_power(
+                                builder,
+                                values[base.0],
+                                exponent_value as i64,
+                                ScalarWidth::F64,
+                            )
+                        } else {
+                            let call =
+                                builder.ins().call(libm.pow, &[values[base.0], values[exponent.0]]);
+                            builder.inst_results(call)[0]
+                        }
+                    }
+                    _ => {
+                        let call =
+                            builder.ins().call(libm.pow, &[values[base.0], values[exponent.0]]);
+                        builder.inst_results(call)[0]
+                    }
+                },
+                NodeKey::Log(base, argument) => {
+                    // log_b(x) = ln(x) / ln(b)
+                    let ln_base_call = builder.ins().call(libm.ln, &[values[base.0]]);
+                    let ln_base = builder.inst_results(ln_base_call)[0];
+                    let ln_argument_call = builder.ins().call(libm.ln, &[values[argument.0]]);
+                    let ln_argument = builder.inst_results(ln_argument_call)[0];
+                    builder.ins().fdiv(ln_argument, ln_base)
+                }
+                NodeKey::Less(left, right) => {
+                    let condition =
+                        builder
+                            .ins()
+                            .fcmp(FloatCC::LessThan, values[left.0], values[right.0]);
+                    ComparisonNode::bool_to_f64(builder, condition, ScalarWidth::F64)
+                }
+                NodeKey::LessEq(left, right) => {
+                    let condition = builder.ins().fcmp(
+                        FloatCC::LessThanOrEqual,
+                        values[left.0],
+                        values[right.0],
+                    );
+                    ComparisonNode::bool_to_f64(builder, condition, ScalarWidth::F64)
+                }
+                NodeKey::Greater(left, right) => {
+                    let condition = builder.ins().fcmp(
+                        FloatCC::GreaterThan,
+                        values[left.0],
+                        values[right.0],
+                    );
+                    ComparisonNode::bool_to_f64(builder, condition, ScalarWidth::F64)
+                }
+                NodeKey::Equal(left, right) => {
+                    let condition =
+                        builder
+                            .ins()
+                            .fcmp(FloatCC::Equal, values[left.0], values[right.0]);
+                    ComparisonNode::bool_to_f64(builder, condition, ScalarWidth::F64)
+                }
+                NodeKey::Select(cond, if_true, if_false) => {
+                    let condition = TernaryNode::to_condition(builder, values[cond.0], ScalarWidth::F64);
+                    builder
+                        .ins()
+                        .select(condition, values[if_true.0], values[if_false.0])
+                }
+            };
+            values.push(value);
+        }
+
+        values
+    }
+}
+
+impl Expr {
+    /// # Optimize
+    /// Fold every constant-only subtree (e.g. `exp(ln(2.0))`) down to a single value. This is the
+    /// tree-rewriting half of the optimization pass `compile_nd_cse` runs before JIT-compiling;
+    /// the other half, common-subexpression elimination, is applied separately at JIT time by
+    /// hash-consing into an [`ExprDag`] (see [`hash_cons`]) rather than rewritten back into the
+    /// tree, since a plain `Expr` tree has no way to represent a node shared by two parents
+    /// without reference-counting the whole tree.
+    pub fn optimize(&self) -> Expr {
+        fold_constants(self)
+    }
+
+    /// # Compile nd with CSE
+    /// Optimize `self` (see [`Expr::optimize`]), hash-cons the result into an [`ExprDag`], and
+    /// JIT-compile it, reusing one Cranelift `Value` per structurally-distinct subexpression
+    /// rather than re-emitting it for every occurrence in the original tree. Behaves like
+    /// `Expression::compile_nd`, except that constant subtrees are folded and shared
+    /// subexpressions (e.g. `(x+y)*(x+y)`) are evaluated once instead of once per occurrence.
+    pub fn compile_nd_cse(&self) -> Result<CompiledExpressionND, ExprParsingError> {
+        let optimized = self.optimize();
+
+        let mut dag = ExprDag::new();
+        let root = hash_cons(&optimized, &mut dag)?;
+
+        let isa = InstructionSetArchitecture::current_platform();
+        let parameters = vec![isa.pointer_type(), types::I64];
+        let return_type = types::F64;
+        let mut jit_helper = JITHelper::new(isa, parameters, return_type);
+
+        // Import the libm transcendentals before taking out the function builder, since both need
+        // a mutable borrow of the JITHelper's function context.
+        let libm = jit_helper.libm_imports();
+
+        {
+            let mut builder = jit_helper.function_builder();
+
+            let entry_block = builder.create_block();
+            builder.append_block_params_for_function_params(entry_block);
+            builder.switch_to_block(entry_block);
+            builder.seal_block(entry_block);
+
+            let params_slice = builder.block_params(entry_block);
+            let parameters = params_slice.to_vec();
+
+            let values = dag.build_jit_nd(&mut builder, &parameters, &libm);
+            builder.ins().return_(&[values[root.0]]);
+            builder.finalize();
+        }
+
+        let (module, code) = jit_helper.finalize();
+        let function = unsafe { std::mem::transmute::<_, fn(*const f64, usize) -> f64>(code) };
+        Ok(CompiledFunction::new(module, function))
+    }
+
+    /// # Compile gradient nd
+    /// Symbolically differentiate `self` with respect to every one of its variables (see
+    /// [`Expr::gradient`]), hash-cons all of the resulting derivative trees into a single shared
+    /// [`ExprDag`], and JIT-compile the whole gradient as one function. Sharing one DAG across
+    /// every partial derivative means a subexpression common to more than one of them (e.g. `x*y`
+    /// showing up in both ∂f/∂x and ∂f/∂y) is evaluated once per call rather than once per
+    /// variable. The compiled function writes one partial derivative per variable, in index
+    /// order, through the caller-provided `*mut f64` output buffer, which must be at least
+    /// `num_variables()` long.
+    pub fn compile_gradient_nd(&self) -> Result<CompiledGradientND, ExprParsingError> {
+        let gradient = self.gradient()?;
+
+        let mut dag = ExprDag::new();
+        let roots: Vec<NodeId> = gradient
+            .iter()
+            .map(|partial| hash_cons(&partial.optimize(), &mut dag))
+            .collect::<Result<_, _>>()?;
+
+        let isa = InstructionSetArchitecture::current_platform();
+        let parameters = vec![isa.pointer_type(), isa.pointer_type(), types::I64];
+        let mut jit_helper = JITHelper::new_void(isa, parameters);
+
+        // Import the libm transcendentals before taking out the function builder, since both need
+        // a mutable borrow of the JITHelper's function context.
+        let libm = jit_helper.libm_imports();
+
+        {
+            let mut builder = jit_helper.function_builder();
+
+            let entry_block = builder.create_block();
+            builder.append_block_params_for_function_params(entry_block);
+            builder.switch_to_block(entry_block);
+            builder.seal_block(entry_block);
+
+            let params_slice = builder.block_params(entry_block);
+            let args_ptr = params_slice[0];
+            let out_ptr = params_slice[1];
+            let cols = params_slice[2];
+
+            let values = dag.build_jit_nd(&mut builder, &[args_ptr, cols], &libm);
+            for (index, root) in roots.iter().enumerate() {
+                builder
+                    .ins()
+                    .store(MemFlags::new(), values[root.0], out_ptr, (index * 8) as i32);
+            }
+            builder.ins().return_(&[]);
+            builder.finalize();
+        }
+
+        let (module, code) = jit_helper.finalize();
+        let function =
+            unsafe { std::mem::transmute::<_, fn(*const f64, *mut f64, usize)>(code) };
+        Ok(CompiledFunction::new(module, function))
+    }
+}
+
+/// # Fold constants
+/// Recursively fold any subtree whose value doesn't depend on a variable into a single
+/// `Constant` leaf, computed directly in Rust rather than through the JIT. Constant-only chains
+/// like `exp(ln(2.0))` collapse to one `f64const` instead of a full instruction sequence. Also
+/// applies algebraic identities (`x+0`, `x*1`, `x/1`, `x^1`, `x^0`) that drop an operand entirely,
+/// since those only need *one* side to be constant rather than both.
+fn fold_constants(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Initialized(initialized) => fold_constants_initialized(initialized),
+        Expr::Uninitialized(_) => {
+            panic!("cannot fold constants in an uninitialized expression; initialize it first")
+        }
+    }
+}
+
+fn fold_constants_initialized(expr: &InitializedExpr) -> Expr {
+    match expr {
+        InitializedExpr::Leaf(leaf) => leaf.clone().to_expr(),
+        InitializedExpr::Unary(unary) => fold_constants_unary(unary),
+        InitializedExpr::Binary(binary) => fold_constants_binary(binary),
+        InitializedExpr::Comparison(comparison) => fold_constants_comparison(comparison),
+        InitializedExpr::Ternary(ternary) => fold_constants_ternary(ternary),
+        InitializedExpr::Let {
+            name_index,
+            value,
+            body,
+        } => InitializedExpr::Let {
+            name_index: *name_index,
+            value: Box::new(fold_constants(value)),
+            body: Box::new(fold_constants(body)),
+        }
+        .to_expr(),
+    }
+}
+
+/// # As constant
+/// If `expr` is (now) a constant leaf, return its value.
+fn as_constant(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Initialized(InitializedExpr::Leaf(InitializedLeaf::Constant(value))) => Some(*value),
+        _ => None,
+    }
+}
+
+fn fold_constants_unary(unary: &UnaryNode) -> Expr {
+    let (inner, op): (&Expr, fn(f64) -> f64) = match unary {
+        UnaryNode::Negate(inner) => (inner, |x| -x),
+        UnaryNode::Sqrt(inner) => (inner, f64::sqrt),
+        UnaryNode::Sin(inner) => (inner, f64::sin),
+        UnaryNode::Cos(inner) => (inner, f64::cos),
+        UnaryNode::Exp(inner) => (inner, f64::exp),
+        UnaryNode::Ln(inner) => (inner, f64::ln),
+    };
+
+    let folded_inner = fold_constants(inner);
+    if let Some(value) = as_constant(&folded_inner) {
+        return InitializedLeaf::Constant(op(value)).to_expr();
+    }
+
+    let rebuild: fn(Box<Expr>) -> UnaryNode = match unary {
+        UnaryNode::Negate(_) => UnaryNode::Negate,
+        UnaryNode::Sqrt(_) => UnaryNode::Sqrt,
+        UnaryNode::Sin(_) => UnaryNode::Sin,
+        UnaryNode::Cos(_) => UnaryNode::Cos,
+        UnaryNode::Exp(_) => UnaryNode::Exp,
+        UnaryNode::Ln(_) => UnaryNode::Ln,
+    };
+    rebuild(Box::new(folded_inner)).to_expr(true)
+}
+
+fn fold_constants_binary(binary: &BinaryNode) -> Expr {
+    let (left, right, op): (&Expr, &Expr, fn(f64, f64) -> f64) = match binary {
+        BinaryNode::Add(left, right) => (left, right, |a, b| a + b),
+        BinaryNode::Subtract(left, right) => (left, right, |a, b| a - b),
+        BinaryNode::Multiply(left, right) => (left, right, |a, b| a * b),
+        BinaryNode::Frac(left, right) => (left, right, |a, b| a / b),
+        BinaryNode::Pow(base, exponent) => (base, exponent, |base, exponent| base.powf(exponent)),
+        BinaryNode::Log(base, argument) => (base, argument, |base, argument| argument.log(base)),
+    };
+
+    let folded_left = fold_constants(left);
+    let folded_right = fold_constants(right);
+    if let (Some(left_value), Some(right_value)) =
+        (as_constant(&folded_left), as_constant(&folded_right))
+    {
+        return InitializedLeaf::Constant(op(left_value, right_value)).to_expr();
+    }
+    if let Some(identity) = algebraic_identity(binary, &folded_left, &folded_right) {
+        return identity;
+    }
+
+    let rebuild: fn(Box<Expr>, Box<Expr>) -> BinaryNode = match binary {
+        BinaryNode::Add(_, _) => BinaryNode::Add,
+        BinaryNode::Subtract(_, _) => BinaryNode::Subtract,
+        BinaryNode::Multiply(_, _) => BinaryNode::Multiply,
+        BinaryNode::Frac(_, _) => BinaryNode::Frac,
+        BinaryNode::Pow(_, _) => BinaryNode::Pow,
+        BinaryNode::Log(_, _) => BinaryNode::Log,
+    };
+    rebuild(Box::new(folded_left), Box::new(folded_right)).to_expr(true)
+}
+
+/// # Algebraic identity
+/// Simplify `x+0`, `x*1`, `x*0`, `x/1`, `x^1`, and `x^0` whenever just *one* operand is a known
+/// constant, catching identities plain constant folding can't (it only fires once *both* operands
+/// fold down to constants).
+fn algebraic_identity(binary: &BinaryNode, left: &Expr, right: &Expr) -> Option<Expr> {
+    let left_value = as_constant(left);
+    let right_value = as_constant(right);
+
+    match binary {
+        BinaryNode::Add(_, _) => {
+            if right_value == Some(0.0) {
+                return Some(left.clone());
+            }
+            if left_value == Some(0.0) {
+                return Some(right.clone());
+            }
+        }
+        BinaryNode::Multiply(_, _) => {
+            if left_value == Some(0.0) || right_value == Some(0.0) {
+                return Some(InitializedLeaf::Constant(0.0).to_expr());
+            }
+            if right_value == Some(1.0) {
+                return Some(left.clone());
+            }
+            if left_value == Some(1.0) {
+                return Some(right.clone());
+            }
+        }
+        BinaryNode::Frac(_, _) => {
+            if right_value == Some(1.0) {
+                return Some(left.clone());
+            }
+        }
+        BinaryNode::Pow(_, _) => {
+            if right_value == Some(1.0) {
+                return Some(left.clone());
+            }
+            if right_value == Some(0.0) {
+                return Some(InitializedLeaf::Constant(1.0).to_expr());
+            }
+        }
+        BinaryNode::Subtract(_, _) | BinaryNode::Log(_, _) => {}
+    }
+
+    None
+}
+
+/// # Fold constants comparison
+/// Fold a comparison whose operands are both constant down to a `1.0`/`0.0` leaf, the same
+/// encoding [`ComparisonNode::evaluate`] produces.
+fn fold_constants_comparison(comparison: &ComparisonNode) -> Expr {
+    let (left, right, holds): (&Expr, &Expr, fn(f64, f64) -> bool) = match comparison {
+        ComparisonNode::Less(left, right) => (left, right, |a, b| a < b),
+        ComparisonNode::LessEq(left, right) => (left, right, |a, b| a <= b),
+        ComparisonNode::Greater(left, right) => (left, right, |a, b| a > b),
+        ComparisonNode::Equal(left, right) => (left, right, |a, b| a == b),
+    };
+
+    let folded_left = fold_constants(left);
+    let folded_right = fold_constants(right);
+    if let (Some(left_value), Some(right_value)) =
+        (as_constant(&folded_left), as_constant(&folded_right))
+    {
+        let value = if holds(left_value, right_value) { 1.0 } else { 0.0 };
+        return InitializedLeaf::Constant(value).to_expr();
+    }
+
+    let rebuild: fn(Box<Expr>, Box<Expr>) -> ComparisonNode = match comparison {
+        ComparisonNode::Less(_, _) => ComparisonNode::Less,
+        ComparisonNode::LessEq(_, _) => ComparisonNode::LessEq,
+        ComparisonNode::Greater(_, _) => ComparisonNode::Greater,
+        ComparisonNode::Equal(_, _) => ComparisonNode::Equal,
+    };
+    rebuild(Box::new(folded_left), Box::new(folded_right)).to_expr(true)
+}
+
+/// # Fold constants ternary
+/// Fold `Select`'s branches, then - if the condition itself folds down to a constant - drop the
+/// branch that can never be taken entirely, the same "known at compile time" shortcut
+/// [`algebraic_identity`] applies to binary operators.
+fn fold_constants_ternary(ternary: &TernaryNode) -> Expr {
+    match ternary {
+        TernaryNode::Select(cond, if_true, if_false) => {
+            let folded_cond = fold_constants(cond);
+            let folded_if_true = fold_constants(if_true);
+            let folded_if_false = fold_constants(if_false);
+
+            if let Some(cond_value) = as_constant(&folded_cond) {
+                return if cond_value != 0.0 {
+                    folded_if_true
+                } else {
+                    folded_if_false
+                };
+            }
+
+            TernaryNode::Select(
+                Box::new(folded_cond),
+                Box::new(folded_if_true),
+                Box::new(folded_if_false),
+            )
+            .to_expr(true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::initialized_variable::InitializedVariable;
+    use crate::structs::uninitialized_variable::UninitializedVariable;
+
+    fn var(index: usize) -> Expr {
+        InitializedLeaf::Variable(InitializedVariable::new(
+            UninitializedVariable::new(format!("var_{index}")),
+            index,
+        ))
+        .to_expr()
+    }
+
+    #[test]
+    fn test_hash_cons_dedupes_shared_subexpression() {
+        // sin(x) appears twice; hash-consing should intern it only once, alongside the
+        // `Variable(x)` it reads from, so the DAG has 3 nodes: x, sin(x), and the Add node - not
+        // the 5 a naive tree walk would produce.
+        let sin_x = UnaryNode::Sin(Box::new(var(0))).to_expr(true);
+        let expr = BinaryNode::Add(Box::new(sin_x.clone()), Box::new(sin_x)).to_expr(true);
+
+        let mut dag = ExprDag::new();
+        hash_cons(&expr, &mut dag).unwrap();
+
+        assert_eq!(dag.len(), 3);
+    }
+
+    #[test]
+    fn test_compile_nd_cse_rejects_let_binding() {
+        // let y = x in y + y; hash-consing can't yet express the scope a let needs, so
+        // compile_nd_cse should return a typed error rather than panicking.
+        let bound_y = InitializedLeaf::Bound(0).to_expr();
+        let body = BinaryNode::Add(Box::new(bound_y.clone()), Box::new(bound_y)).to_expr(true);
+        let expr = InitializedExpr::let_binding(0, var(0), body).to_expr();
+
+        assert!(expr.compile_nd_cse().is_err());
+    }
+
+    #[test]
+    fn test_compile_nd_cse_matches_compile_nd() {
+        use crate::traits::expression::Expression;
+
+        // f(x) = sin(x) * sin(x) + cos(sin(x)), which repeats the `sin(x)` subtree.
+        let sin_x = UnaryNode::Sin(Box::new(var(0)));
+        let expr = InitializedExpr::Binary(BinaryNode::Add(
+            Box::new(
+                InitializedExpr::Binary(BinaryNode::Multiply(
+                    Box::new(sin_x.to_expr(true)),
+                    Box::new(sin_x.to_expr(true)),
+                ))
+                .to_expr(),
+            ),
+            Box::new(InitializedExpr::Unary(UnaryNode::Cos(Box::new(sin_x.to_expr(true)))).to_expr()),
+        ));
+
+        let plain = expr.compile_nd().unwrap();
+        let cse = expr.to_expr().compile_nd_cse().unwrap();
+
+        let variables = vec![0.7_f64];
+        let expected = variables[0].sin() * variables[0].sin() + variables[0].sin().cos();
+
+        assert!((plain.call(variables.as_ptr(), variables.len()) - expected).abs() < 1e-12);
+        assert!((cse.call(variables.as_ptr(), variables.len()) - expected).abs() < 1e-12);
+    }
+}
@@ -1,9 +1,27 @@
-use super::{expr::Expr, initialized_expr::InitializedExpr, uninitialized_expr::UninitializedExpr};
-use crate::traits::{
-    expression::Expression, expression_compiler::ExpressionCompiler,
-    expression_node::ExpressionNode,
+use super::{
+    expr::Expr, initialized_expr::InitializedExpr, initialized_leaf::InitializedLeaf,
+    uninitialized_expr::UninitializedExpr,
 };
+use crate::traits::{expression::Expression, expression_node::ExpressionNode};
+#[cfg(feature = "jit")]
+use crate::enums::scalar_width::ScalarWidth;
+#[cfg(feature = "jit")]
+use crate::structs::libm_imports::LibmImports;
+#[cfg(feature = "jit")]
+use crate::traits::expression_compiler::ExpressionCompiler;
+#[cfg(feature = "gpu")]
+use crate::traits::expression_shader_compiler::ExpressionShaderCompiler;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::types;
+#[cfg(feature = "jit")]
 use cranelift_codegen::ir::{InstBuilder, Value};
+#[cfg(feature = "jit")]
+use cranelift_frontend::FunctionBuilder;
+
+/// The largest integer exponent `Pow` will expand into repeated `fmul` rather than a `pow`
+/// libcall. Picked to comfortably cover physics-fit polynomial terms while keeping the expanded
+/// instruction sequence short.
+const MAX_INTEGER_POWER_EXPANSION: f64 = 64.0;
 
 /// # BinaryNode
 /// A node that has exactly two child nodes.
@@ -52,7 +70,10 @@ impl BinaryNode {
             BinaryNode::Log(_, right) => right,
         }
     }
+}
 
+#[cfg(feature = "jit")]
+impl BinaryNode {
     fn expression_value(
         &self,
         builder: &mut cranelift_frontend::FunctionBuilder<'_>,
@@ -67,28 +88,171 @@ impl BinaryNode {
             _ => unimplemented!(),
         }
     }
+
+    /// # Expression value ND
+    /// Like `expression_value`, but for the ND build path, where `libm` carries the `FuncRef`s
+    /// needed to call out to the host math library for `Pow`/`Log`, which have no hardware
+    /// instruction. `libm`'s imports are all `f64`-signature, so at `width == F32` the operands
+    /// are widened before the call and the result narrowed back afterwards.
+    fn expression_value_nd(
+        &self,
+        builder: &mut FunctionBuilder<'_>,
+        left_value: Value,
+        right_value: Value,
+        libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value {
+        match self {
+            BinaryNode::Add(_, _) | BinaryNode::Subtract(_, _) | BinaryNode::Multiply(_, _)
+            | BinaryNode::Frac(_, _) => self.expression_value(builder, left_value, right_value),
+
+            // Known-small integer exponents expand into repeated `fmul`, avoiding a `pow`
+            // libcall for the common case (e.g. `x^2` in a polynomial fit).
+            BinaryNode::Pow(_, exponent) => match integer_exponent(exponent) {
+                Some(power) => integer_power(builder, left_value, power, width),
+                None => {
+                    let wide_left = width.widen_to_f64(builder, left_value);
+                    let wide_right = width.widen_to_f64(builder, right_value);
+                    let call = builder.ins().call(libm.pow, &[wide_left, wide_right]);
+                    let result = builder.inst_results(call)[0];
+                    width.narrow_from_f64(builder, result)
+                }
+            },
+
+            // log_b(x) = ln(x) / ln(b)
+            BinaryNode::Log(_, _) => {
+                let wide_left = width.widen_to_f64(builder, left_value);
+                let wide_right = width.widen_to_f64(builder, right_value);
+                let ln_base_call = builder.ins().call(libm.ln, &[wide_left]);
+                let ln_base = builder.inst_results(ln_base_call)[0];
+                let ln_argument_call = builder.ins().call(libm.ln, &[wide_right]);
+                let ln_argument = builder.inst_results(ln_argument_call)[0];
+                let result = builder.ins().fdiv(ln_argument, ln_base);
+                width.narrow_from_f64(builder, result)
+            }
+        }
+    }
+
+    /// # Lane wise expression value ND
+    /// Apply `expression_value_nd` to every lane of `left_value`/`right_value` independently,
+    /// returning a vector of the same width built back up lane by lane. Used for `Pow`/`Log`,
+    /// which have no vectorized libm form.
+    fn lane_wise_expression_value_nd(
+        &self,
+        builder: &mut FunctionBuilder<'_>,
+        left_value: Value,
+        right_value: Value,
+        libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value {
+        let lane_count = lanes.lane_count();
+        let zero = builder.ins().f64const(0.0);
+        let mut result = builder.ins().splat(lanes, zero);
+
+        for lane in 0..lane_count {
+            let left_scalar = builder.ins().extractlane(left_value, lane as u8);
+            let right_scalar = builder.ins().extractlane(right_value, lane as u8);
+            let scalar_result = self.expression_value_nd(
+                builder,
+                left_scalar,
+                right_scalar,
+                libm,
+                ScalarWidth::F64,
+            );
+            result = builder.ins().insertlane(result, scalar_result, lane as u8);
+        }
+
+        result
+    }
+}
+
+/// # Integer exponent
+/// If `exponent` is a constant leaf holding a small integer value, return it so `Pow` can expand
+/// into repeated multiplication instead of a `pow` libcall.
+#[cfg(feature = "jit")]
+fn integer_exponent(exponent: &Expr) -> Option<i64> {
+    match exponent {
+        Expr::Initialized(InitializedExpr::Leaf(InitializedLeaf::Constant(value)))
+            if value.fract() == 0.0 && value.abs() <= MAX_INTEGER_POWER_EXPANSION =>
+        {
+            Some(*value as i64)
+        }
+        _ => None,
+    }
+}
+
+/// # Integer power
+/// Expand `base^power` into repeated `fmul`, for a small integer `power` known at compile time.
+/// Shared with [`crate::dag::ExprDag::build_jit_nd`], which applies the same fast path once a
+/// `Pow` node's exponent has been hash-consed down to a `Constant` (always at `f64` width, since
+/// the DAG path doesn't go through `ScalarWidth` yet).
+#[cfg(feature = "jit")]
+pub(crate) fn integer_power(
+    builder: &mut FunctionBuilder<'_>,
+    base: Value,
+    power: i64,
+    width: ScalarWidth,
+) -> Value {
+    let mut result = width.const_value(builder, 1.0);
+    for _ in 0..power.unsigned_abs() {
+        result = builder.ins().fmul(result, base);
+    }
+
+    if power < 0 {
+        let one = width.const_value(builder, 1.0);
+        result = builder.ins().fdiv(one, result);
+    }
+
+    result
 }
 
+#[cfg(feature = "jit")]
 impl ExpressionCompiler for BinaryNode {
     fn build_jit_nd(
         &self,
         builder: &mut cranelift_frontend::FunctionBuilder,
         parameters: &[Value],
+        libm: &LibmImports,
+        width: ScalarWidth,
     ) -> cranelift_codegen::ir::Value {
         // Start by building the left and right Values, then apply the binary operation.
-        let left_value = self.left().build_jit_nd(builder, parameters);
-        let right_value = self.right().build_jit_nd(builder, parameters);
-        self.expression_value(builder, left_value, right_value)
+        let left_value = self.left().build_jit_nd(builder, parameters, libm, width);
+        let right_value = self.right().build_jit_nd(builder, parameters, libm, width);
+        self.expression_value_nd(builder, left_value, right_value, libm, width)
+    }
+
+    fn build_jit_nd_vec(
+        &self,
+        builder: &mut cranelift_frontend::FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        lanes: types::Type,
+    ) -> cranelift_codegen::ir::Value {
+        // Start by building the left and right Values, then apply the binary operation. The
+        // vector forms of fadd/fsub/fmul/fdiv operate lane-wise, so no extra plumbing is needed
+        // beyond building both children at the requested lane width; `Pow`/`Log` have no vector
+        // libm form, so those fall back to a lane-wise libcall, same as the transcendental
+        // `UnaryNode` variants.
+        let left_value = self.left().build_jit_nd_vec(builder, parameters, libm, lanes);
+        let right_value = self.right().build_jit_nd_vec(builder, parameters, libm, lanes);
+        match self {
+            BinaryNode::Add(_, _) | BinaryNode::Subtract(_, _) | BinaryNode::Multiply(_, _)
+            | BinaryNode::Frac(_, _) => self.expression_value(builder, left_value, right_value),
+            BinaryNode::Pow(_, _) | BinaryNode::Log(_, _) => {
+                self.lane_wise_expression_value_nd(builder, left_value, right_value, libm, lanes)
+            }
+        }
     }
 
     fn build_jit_1d(
         &self,
         builder: &mut cranelift_frontend::FunctionBuilder,
         parameter: Value,
+        libm: &LibmImports,
     ) -> Value {
         // Start by building the left and right Values, then apply the binary operation.
-        let left_value = self.left().build_jit_1d(builder, parameter);
-        let right_value = self.right().build_jit_1d(builder, parameter);
+        let left_value = self.left().build_jit_1d(builder, parameter, libm);
+        let right_value = self.right().build_jit_1d(builder, parameter, libm);
         self.expression_value(builder, left_value, right_value)
     }
 
@@ -97,10 +261,11 @@ impl ExpressionCompiler for BinaryNode {
         builder: &mut cranelift_frontend::FunctionBuilder,
         param_0: Value,
         param_1: Value,
+        libm: &LibmImports,
     ) -> Value {
         // Start by building the left and right Values, then apply the binary operation.
-        let left_value = self.left().build_jit_2d(builder, param_0, param_1);
-        let right_value = self.right().build_jit_2d(builder, param_0, param_1);
+        let left_value = self.left().build_jit_2d(builder, param_0, param_1, libm);
+        let right_value = self.right().build_jit_2d(builder, param_0, param_1, libm);
         self.expression_value(builder, left_value, right_value)
     }
 
@@ -110,14 +275,39 @@ impl ExpressionCompiler for BinaryNode {
         param_0: Value,
         param_1: Value,
         param_2: Value,
+        libm: &LibmImports,
     ) -> Value {
         // Start by building the left and right Values, then apply the binary operation.
-        let left_value = self.left().build_jit_3d(builder, param_0, param_1, param_2);
+        let left_value = self
+            .left()
+            .build_jit_3d(builder, param_0, param_1, param_2, libm);
         let right_value = self
             .right()
-            .build_jit_3d(builder, param_0, param_1, param_2);
+            .build_jit_3d(builder, param_0, param_1, param_2, libm);
         self.expression_value(builder, left_value, right_value)
     }
+
+    fn contains_let(&self) -> bool {
+        self.left().contains_let() || self.right().contains_let()
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl ExpressionShaderCompiler for BinaryNode {
+    fn wgsl_expr(&self, buffer: &str) -> String {
+        let left = self.left().wgsl_expr(buffer);
+        let right = self.right().wgsl_expr(buffer);
+        match self {
+            BinaryNode::Add(_, _) => format!("({left} + {right})"),
+            BinaryNode::Subtract(_, _) => format!("({left} - {right})"),
+            BinaryNode::Multiply(_, _) => format!("({left} * {right})"),
+            BinaryNode::Frac(_, _) => format!("({left} / {right})"),
+            BinaryNode::Pow(_, _) => format!("pow({left}, {right})"),
+            // log_b(x) = ln(x) / ln(b), the same identity BinaryNode::Log's JIT path uses, since
+            // WGSL's `log` builtin is natural log rather than a base-parameterized one.
+            BinaryNode::Log(_, _) => format!("(log({right}) / log({left}))"),
+        }
+    }
 }
 
 impl Expression for BinaryNode {
@@ -128,7 +318,7 @@ impl Expression for BinaryNode {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "jit"))]
 mod tests {
     use crate::functions::variable;
 
@@ -144,7 +334,7 @@ mod tests {
         .unwrap();
 
         let values = vec![1.0, 2.0];
-        assert_eq!(f(values.as_ptr(), values.len()), 3.0);
+        assert_eq!(f.call(values.as_ptr(), values.len()), 3.0);
     }
 
     #[test]
@@ -157,7 +347,7 @@ mod tests {
         .unwrap();
 
         let values = vec![3.0, 4.0];
-        assert_eq!(f(values.as_ptr(), values.len()), 12.0);
+        assert_eq!(f.call(values.as_ptr(), values.len()), 12.0);
     }
 
     #[test]
@@ -170,6 +360,6 @@ mod tests {
         .unwrap();
 
         let values = vec![3.0, 4.0];
-        assert_eq!(f(values.as_ptr(), values.len()), 0.75);
+        assert_eq!(f.call(values.as_ptr(), values.len()), 0.75);
     }
 }
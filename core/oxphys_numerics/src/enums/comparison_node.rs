@@ -0,0 +1,256 @@
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::condcodes::FloatCC;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::types;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::{InstBuilder, Value};
+#[cfg(feature = "jit")]
+use cranelift_frontend::FunctionBuilder;
+
+#[cfg(feature = "jit")]
+use crate::enums::scalar_width::ScalarWidth;
+#[cfg(feature = "jit")]
+use crate::structs::libm_imports::LibmImports;
+#[cfg(feature = "jit")]
+use crate::traits::expression_compiler::ExpressionCompiler;
+#[cfg(feature = "gpu")]
+use crate::traits::expression_shader_compiler::ExpressionShaderCompiler;
+use crate::traits::{expression::Expression, expression_node::ExpressionNode};
+
+use super::{expr::Expr, initialized_expr::InitializedExpr, uninitialized_expr::UninitializedExpr};
+
+/// # ComparisonNode
+/// A node that compares its two child expressions, producing `1.0` if the comparison holds and
+/// `0.0` otherwise - a "branchless boolean" that lives in the same `f64` value space as every
+/// other node, so it can be used standalone (e.g. to build a 0/1 indicator) or as the condition
+/// of a `TernaryNode::Select`.
+#[derive(Debug, Clone)]
+pub enum ComparisonNode {
+    Less(Box<Expr>, Box<Expr>),
+    LessEq(Box<Expr>, Box<Expr>),
+    Greater(Box<Expr>, Box<Expr>),
+    Equal(Box<Expr>, Box<Expr>),
+}
+
+impl ComparisonNode {
+    /// # Left
+    /// Get the left expression.
+    fn left(&self) -> &Expr {
+        match self {
+            ComparisonNode::Less(left, _) => left,
+            ComparisonNode::LessEq(left, _) => left,
+            ComparisonNode::Greater(left, _) => left,
+            ComparisonNode::Equal(left, _) => left,
+        }
+    }
+
+    /// # Right
+    /// Get the right expression.
+    fn right(&self) -> &Expr {
+        match self {
+            ComparisonNode::Less(_, right) => right,
+            ComparisonNode::LessEq(_, right) => right,
+            ComparisonNode::Greater(_, right) => right,
+            ComparisonNode::Equal(_, right) => right,
+        }
+    }
+}
+
+#[cfg(feature = "jit")]
+impl ComparisonNode {
+    /// # Float cc
+    /// The Cranelift floating-point condition code this comparison lowers to.
+    fn float_cc(&self) -> FloatCC {
+        match self {
+            ComparisonNode::Less(_, _) => FloatCC::LessThan,
+            ComparisonNode::LessEq(_, _) => FloatCC::LessThanOrEqual,
+            ComparisonNode::Greater(_, _) => FloatCC::GreaterThan,
+            ComparisonNode::Equal(_, _) => FloatCC::Equal,
+        }
+    }
+
+    /// # Condition value
+    /// Emit the raw Cranelift boolean this comparison produces (`fcmp`'s result type), for
+    /// callers - namely `TernaryNode::Select` - that need a branch condition rather than the
+    /// `1.0`/`0.0` encoding `build_jit_*` returns.
+    fn condition_value(&self, builder: &mut FunctionBuilder, left: Value, right: Value) -> Value {
+        builder.ins().fcmp(self.float_cc(), left, right)
+    }
+
+    /// # Bool to f64
+    /// Convert a Cranelift boolean into the `1.0`/`0.0` encoding every other node's `build_jit_*`
+    /// returns, via `select` rather than a branch. Shared with `crate::dag::ExprDag::build_jit_nd`,
+    /// which applies the same conversion once a comparison has been hash-consed into the DAG
+    /// (always at `f64` width, since the DAG path doesn't go through `ScalarWidth` yet).
+    pub(crate) fn bool_to_f64(
+        builder: &mut FunctionBuilder,
+        condition: Value,
+        width: ScalarWidth,
+    ) -> Value {
+        let one = width.const_value(builder, 1.0);
+        let zero = width.const_value(builder, 0.0);
+        builder.ins().select(condition, one, zero)
+    }
+
+    /// # Lane wise compare
+    /// Apply this comparison to every lane of `left`/`right` independently, returning a vector of
+    /// the same width built back up lane by lane. `fcmp`/`select` only operate on scalars, so this
+    /// is the same lane-wise fallback the transcendental `UnaryNode`/`BinaryNode` variants use.
+    fn lane_wise_compare(
+        &self,
+        builder: &mut FunctionBuilder,
+        left: Value,
+        right: Value,
+        lanes: types::Type,
+    ) -> Value {
+        let lane_count = lanes.lane_count();
+        let zero = builder.ins().f64const(0.0);
+        let mut result = builder.ins().splat(lanes, zero);
+
+        for lane in 0..lane_count {
+            let left_scalar = builder.ins().extractlane(left, lane as u8);
+            let right_scalar = builder.ins().extractlane(right, lane as u8);
+            let condition = self.condition_value(builder, left_scalar, right_scalar);
+            let scalar_result = Self::bool_to_f64(builder, condition, ScalarWidth::F64);
+            result = builder.ins().insertlane(result, scalar_result, lane as u8);
+        }
+
+        result
+    }
+}
+
+impl ExpressionNode for ComparisonNode {
+    fn to_expr(&self, is_initialized: bool) -> Expr {
+        match is_initialized {
+            true => Expr::Initialized(InitializedExpr::Comparison(self.clone())),
+            false => Expr::Uninitialized(UninitializedExpr::Comparison(self.clone())),
+        }
+    }
+}
+
+#[cfg(feature = "jit")]
+impl ExpressionCompiler for ComparisonNode {
+    fn build_jit_nd(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value {
+        let left = self.left().build_jit_nd(builder, parameters, libm, width);
+        let right = self.right().build_jit_nd(builder, parameters, libm, width);
+        let condition = self.condition_value(builder, left, right);
+        Self::bool_to_f64(builder, condition, width)
+    }
+
+    fn build_jit_nd_vec(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value {
+        let left = self.left().build_jit_nd_vec(builder, parameters, libm, lanes);
+        let right = self.right().build_jit_nd_vec(builder, parameters, libm, lanes);
+        self.lane_wise_compare(builder, left, right, lanes)
+    }
+
+    fn build_jit_1d(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameter: Value,
+        libm: &LibmImports,
+    ) -> Value {
+        let left = self.left().build_jit_1d(builder, parameter, libm);
+        let right = self.right().build_jit_1d(builder, parameter, libm);
+        let condition = self.condition_value(builder, left, right);
+        Self::bool_to_f64(builder, condition, ScalarWidth::F64)
+    }
+
+    fn build_jit_2d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        libm: &LibmImports,
+    ) -> Value {
+        let left = self.left().build_jit_2d(builder, param_0, param_1, libm);
+        let right = self.right().build_jit_2d(builder, param_0, param_1, libm);
+        let condition = self.condition_value(builder, left, right);
+        Self::bool_to_f64(builder, condition, ScalarWidth::F64)
+    }
+
+    fn build_jit_3d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        param_2: Value,
+        libm: &LibmImports,
+    ) -> Value {
+        let left = self
+            .left()
+            .build_jit_3d(builder, param_0, param_1, param_2, libm);
+        let right = self
+            .right()
+            .build_jit_3d(builder, param_0, param_1, param_2, libm);
+        let condition = self.condition_value(builder, left, right);
+        Self::bool_to_f64(builder, condition, ScalarWidth::F64)
+    }
+
+    fn contains_let(&self) -> bool {
+        self.left().contains_let() || self.right().contains_let()
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl ComparisonNode {
+    /// # Wgsl operator
+    /// The WGSL infix comparison operator this node lowers to.
+    fn wgsl_operator(&self) -> &'static str {
+        match self {
+            ComparisonNode::Less(_, _) => "<",
+            ComparisonNode::LessEq(_, _) => "<=",
+            ComparisonNode::Greater(_, _) => ">",
+            ComparisonNode::Equal(_, _) => "==",
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl ExpressionShaderCompiler for ComparisonNode {
+    fn wgsl_expr(&self, buffer: &str) -> String {
+        let left = self.left().wgsl_expr(buffer);
+        let right = self.right().wgsl_expr(buffer);
+        // `select(false_value, true_value, condition)` is WGSL's branchless conditional - the
+        // same `1.0`/`0.0` encoding `ComparisonNode::bool_to_f64` produces on the JIT path.
+        format!(
+            "select(0.0, 1.0, {left} {} {right})",
+            self.wgsl_operator()
+        )
+    }
+}
+
+impl Expression for ComparisonNode {
+    fn evaluate(&self, variables: &Vec<f64>) -> f64 {
+        let left = self.left().evaluate(variables);
+        let right = self.right().evaluate(variables);
+        let holds = match self {
+            ComparisonNode::Less(_, _) => left < right,
+            ComparisonNode::LessEq(_, _) => left <= right,
+            ComparisonNode::Greater(_, _) => left > right,
+            ComparisonNode::Equal(_, _) => left == right,
+        };
+        if holds {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn num_variables(&self) -> usize {
+        self.left()
+            .num_variables()
+            .max(self.right().num_variables())
+    }
+}
@@ -1,12 +1,25 @@
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::types;
+#[cfg(feature = "jit")]
 use cranelift_codegen::ir::Value;
-use cranelift_frontend::FunctionBuilder;
+#[cfg(feature = "jit")]
+use cranelift_frontend::{FunctionBuilder, Variable as CraneliftVariable};
 
-use crate::traits::{expression::Expression, expression_compiler::ExpressionCompiler};
+#[cfg(feature = "jit")]
+use crate::enums::scalar_width::ScalarWidth;
+#[cfg(feature = "jit")]
+use crate::structs::libm_imports::LibmImports;
+use crate::traits::expression::Expression;
+#[cfg(feature = "jit")]
+use crate::traits::expression_compiler::ExpressionCompiler;
+#[cfg(feature = "gpu")]
+use crate::traits::expression_shader_compiler::ExpressionShaderCompiler;
 
 use super::{
-    binary_node::BinaryNode, expr::Expr, initialized_leaf::InitializedLeaf, unary_node::UnaryNode,
+    binary_node::BinaryNode, comparison_node::ComparisonNode, expr::Expr,
+    initialized_leaf::InitializedLeaf, ternary_node::TernaryNode, unary_node::UnaryNode,
 };
 
 /// # InitializedExpr
@@ -17,6 +30,18 @@ pub enum InitializedExpr {
     Leaf(InitializedLeaf),
     Unary(UnaryNode),
     Binary(BinaryNode),
+    Comparison(ComparisonNode),
+    Ternary(TernaryNode),
+
+    /// A let-binding: evaluate `value`, bind it to `name_index` for the duration of `body`, then
+    /// evaluate `body`. `name_index` shares the same index space as free variables (see
+    /// [`crate::structs::initialized_variable::InitializedVariable`]), so a let-bound name and a
+    /// free variable can never collide.
+    Let {
+        name_index: usize,
+        value: Box<Expr>,
+        body: Box<Expr>,
+    },
 }
 
 impl Add for InitializedExpr {
@@ -77,30 +102,171 @@ impl InitializedExpr {
     pub fn to_expr(self) -> Expr {
         Expr::Initialized(self)
     }
+
+    /// # Let binding
+    /// Bind `value` to `name_index` for the duration of `body`, the way `let name = value in
+    /// body` would in the parsed surface syntax.
+    pub fn let_binding(name_index: usize, value: Expr, body: Expr) -> InitializedExpr {
+        InitializedExpr::Let {
+            name_index,
+            value: Box::new(value),
+            body: Box::new(body),
+        }
+    }
 }
 
+/// # Compile expr
+/// Dispatch a single build_jit step to a child `Expr`, panicking if it's uninitialized. A let's
+/// `value` and `body` are plain `Expr`s rather than `InitializedExpr`s (matching the `Box<Expr>`
+/// children used by `UnaryNode`/`BinaryNode`), so every build_jit_* arm below needs this one
+/// extra level of unwrapping.
+#[cfg(feature = "jit")]
+fn compile_expr(
+    expr: &Expr,
+    builder: &mut FunctionBuilder,
+    parameters: &[Value],
+    libm: &LibmImports,
+    width: ScalarWidth,
+) -> Value {
+    match expr {
+        Expr::Initialized(initialized) => {
+            initialized.build_jit_nd(builder, parameters, libm, width)
+        }
+        Expr::Uninitialized(_) => {
+            panic!("cannot JIT-compile an uninitialized expression; initialize it first")
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl ExpressionShaderCompiler for InitializedExpr {
+    fn wgsl_expr(&self, buffer: &str) -> String {
+        match self {
+            InitializedExpr::Leaf(leaf) => leaf.wgsl_expr(buffer),
+            InitializedExpr::Unary(unary) => unary.wgsl_expr(buffer),
+            InitializedExpr::Binary(binary) => binary.wgsl_expr(buffer),
+            InitializedExpr::Comparison(comparison) => comparison.wgsl_expr(buffer),
+            InitializedExpr::Ternary(ternary) => ternary.wgsl_expr(buffer),
+            InitializedExpr::Let { .. } => {
+                unimplemented!("let-bindings are only supported through the JIT path for now")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "jit")]
 impl ExpressionCompiler for InitializedExpr {
-    fn build_jit_nd(&self, builder: &mut FunctionBuilder, parameters: &[Value]) -> Value {
+    fn build_jit_nd(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value {
+        match self {
+            InitializedExpr::Leaf(leaf) => leaf.build_jit_nd(builder, parameters, libm, width),
+            InitializedExpr::Unary(unary) => unary.build_jit_nd(builder, parameters, libm, width),
+            InitializedExpr::Binary(binary) => {
+                binary.build_jit_nd(builder, parameters, libm, width)
+            }
+            InitializedExpr::Comparison(comparison) => {
+                comparison.build_jit_nd(builder, parameters, libm, width)
+            }
+            InitializedExpr::Ternary(ternary) => {
+                ternary.build_jit_nd(builder, parameters, libm, width)
+            }
+            InitializedExpr::Let {
+                name_index,
+                value,
+                body,
+            } => {
+                let variable = CraneliftVariable::new(*name_index);
+                builder.declare_var(variable, width.cranelift_type());
+                let value_value = compile_expr(value, builder, parameters, libm, width);
+                builder.def_var(variable, value_value);
+                compile_expr(body, builder, parameters, libm, width)
+            }
+        }
+    }
+
+    fn build_jit_nd_vec(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value {
         match self {
-            InitializedExpr::Leaf(leaf) => leaf.build_jit_nd(builder, parameters),
-            InitializedExpr::Unary(unary) => unary.build_jit_nd(builder, parameters),
-            InitializedExpr::Binary(binary) => binary.build_jit_nd(builder, parameters),
+            InitializedExpr::Leaf(leaf) => leaf.build_jit_nd_vec(builder, parameters, libm, lanes),
+            InitializedExpr::Unary(unary) => {
+                unary.build_jit_nd_vec(builder, parameters, libm, lanes)
+            }
+            InitializedExpr::Binary(binary) => {
+                binary.build_jit_nd_vec(builder, parameters, libm, lanes)
+            }
+            InitializedExpr::Comparison(comparison) => {
+                comparison.build_jit_nd_vec(builder, parameters, libm, lanes)
+            }
+            InitializedExpr::Ternary(ternary) => {
+                ternary.build_jit_nd_vec(builder, parameters, libm, lanes)
+            }
+            InitializedExpr::Let { .. } => {
+                unimplemented!("let-bindings are only supported through build_jit_nd for now")
+            }
+        }
+    }
+
+    fn contains_let(&self) -> bool {
+        match self {
+            InitializedExpr::Leaf(_) => false,
+            InitializedExpr::Unary(unary) => unary.contains_let(),
+            InitializedExpr::Binary(binary) => binary.contains_let(),
+            InitializedExpr::Comparison(comparison) => comparison.contains_let(),
+            InitializedExpr::Ternary(ternary) => ternary.contains_let(),
+            InitializedExpr::Let { .. } => true,
         }
     }
 
-    fn build_jit_1d(&self, builder: &mut FunctionBuilder, parameter: Value) -> Value {
+    fn build_jit_1d(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameter: Value,
+        libm: &LibmImports,
+    ) -> Value {
         match self {
-            InitializedExpr::Leaf(leaf) => leaf.build_jit_1d(builder, parameter),
-            InitializedExpr::Unary(unary) => unary.build_jit_1d(builder, parameter),
-            InitializedExpr::Binary(binary) => binary.build_jit_1d(builder, parameter),
+            InitializedExpr::Leaf(leaf) => leaf.build_jit_1d(builder, parameter, libm),
+            InitializedExpr::Unary(unary) => unary.build_jit_1d(builder, parameter, libm),
+            InitializedExpr::Binary(binary) => binary.build_jit_1d(builder, parameter, libm),
+            InitializedExpr::Comparison(comparison) => {
+                comparison.build_jit_1d(builder, parameter, libm)
+            }
+            InitializedExpr::Ternary(ternary) => ternary.build_jit_1d(builder, parameter, libm),
+            InitializedExpr::Let { .. } => {
+                unimplemented!("let-bindings are only supported through build_jit_nd for now")
+            }
         }
     }
 
-    fn build_jit_2d(&self, builder: &mut FunctionBuilder, param_0: Value, param_1: Value) -> Value {
+    fn build_jit_2d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        libm: &LibmImports,
+    ) -> Value {
         match self {
-            InitializedExpr::Leaf(leaf) => leaf.build_jit_2d(builder, param_0, param_1),
-            InitializedExpr::Unary(unary) => unary.build_jit_2d(builder, param_0, param_1),
-            InitializedExpr::Binary(binary) => binary.build_jit_2d(builder, param_0, param_1),
+            InitializedExpr::Leaf(leaf) => leaf.build_jit_2d(builder, param_0, param_1, libm),
+            InitializedExpr::Unary(unary) => unary.build_jit_2d(builder, param_0, param_1, libm),
+            InitializedExpr::Binary(binary) => binary.build_jit_2d(builder, param_0, param_1, libm),
+            InitializedExpr::Comparison(comparison) => {
+                comparison.build_jit_2d(builder, param_0, param_1, libm)
+            }
+            InitializedExpr::Ternary(ternary) => {
+                ternary.build_jit_2d(builder, param_0, param_1, libm)
+            }
+            InitializedExpr::Let { .. } => {
+                unimplemented!("let-bindings are only supported through build_jit_nd for now")
+            }
         }
     }
 
@@ -110,23 +276,148 @@ impl ExpressionCompiler for InitializedExpr {
         param_0: Value,
         param_1: Value,
         param_2: Value,
+        libm: &LibmImports,
     ) -> Value {
         match self {
-            InitializedExpr::Leaf(leaf) => leaf.build_jit_3d(builder, param_0, param_1, param_2),
-            InitializedExpr::Unary(unary) => unary.build_jit_3d(builder, param_0, param_1, param_2),
+            InitializedExpr::Leaf(leaf) => {
+                leaf.build_jit_3d(builder, param_0, param_1, param_2, libm)
+            }
+            InitializedExpr::Unary(unary) => {
+                unary.build_jit_3d(builder, param_0, param_1, param_2, libm)
+            }
             InitializedExpr::Binary(binary) => {
-                binary.build_jit_3d(builder, param_0, param_1, param_2)
+                binary.build_jit_3d(builder, param_0, param_1, param_2, libm)
+            }
+            InitializedExpr::Comparison(comparison) => {
+                comparison.build_jit_3d(builder, param_0, param_1, param_2, libm)
+            }
+            InitializedExpr::Ternary(ternary) => {
+                ternary.build_jit_3d(builder, param_0, param_1, param_2, libm)
+            }
+            InitializedExpr::Let { .. } => {
+                unimplemented!("let-bindings are only supported through build_jit_nd for now")
             }
         }
     }
 }
 
 impl Expression for InitializedExpr {
+    fn evaluate(&self, variables: &Vec<f64>) -> f64 {
+        match self {
+            InitializedExpr::Leaf(leaf) => leaf.evaluate(variables),
+            InitializedExpr::Unary(unary) => unary.evaluate(variables),
+            InitializedExpr::Binary(binary) => binary.evaluate(variables),
+            InitializedExpr::Comparison(comparison) => comparison.evaluate(variables),
+            InitializedExpr::Ternary(ternary) => ternary.evaluate(variables),
+            InitializedExpr::Let {
+                name_index,
+                value,
+                body,
+            } => {
+                let bound_value = expr_evaluate(value, variables);
+
+                // `name_index` shares the free-variable index space (see `InitializedExpr::Let`'s
+                // doc comment), so "push" the bound value onto the environment by writing it into
+                // that slot of a scoped copy of `variables`, evaluate `body` against the extended
+                // copy, then let it drop - the pop a real scope stack would need, since `variables`
+                // itself is never mutated.
+                let mut scope = variables.clone();
+                if *name_index >= scope.len() {
+                    scope.resize(*name_index + 1, 0.0);
+                }
+                scope[*name_index] = bound_value;
+
+                expr_evaluate(body, &scope)
+            }
+        }
+    }
+
     fn num_variables(&self) -> usize {
         match self {
             InitializedExpr::Leaf(leaf) => leaf.num_variables(),
             InitializedExpr::Unary(unary) => unary.num_variables(),
             InitializedExpr::Binary(binary) => binary.num_variables(),
+            InitializedExpr::Comparison(comparison) => comparison.num_variables(),
+            InitializedExpr::Ternary(ternary) => ternary.num_variables(),
+            InitializedExpr::Let {
+                name_index,
+                value,
+                body,
+            } => (*name_index + 1)
+                .max(expr_num_variables(value))
+                .max(expr_num_variables(body)),
+        }
+    }
+}
+
+/// # Expr num variables
+/// Mirror of [`Expression::num_variables`] for a plain `Expr`, needed because a let's `value` and
+/// `body` haven't been unwrapped down to `InitializedExpr` yet.
+fn expr_num_variables(expr: &Expr) -> usize {
+    match expr {
+        Expr::Initialized(initialized) => initialized.num_variables(),
+        Expr::Uninitialized(_) => {
+            panic!("cannot count variables in an uninitialized expression; initialize it first")
         }
     }
 }
+
+/// # Expr evaluate
+/// Mirror of [`Expression::evaluate`] for a plain `Expr`, needed because a let's `value` and
+/// `body` haven't been unwrapped down to `InitializedExpr` yet.
+fn expr_evaluate(expr: &Expr, variables: &Vec<f64>) -> f64 {
+    match expr {
+        Expr::Initialized(initialized) => initialized.evaluate(variables),
+        Expr::Uninitialized(_) => {
+            panic!("cannot evaluate an uninitialized expression; initialize it first")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::binary_node::BinaryNode;
+    use crate::structs::initialized_variable::InitializedVariable;
+    use crate::structs::uninitialized_variable::UninitializedVariable;
+    use crate::traits::expression_node::ExpressionNode;
+
+    fn var(index: usize) -> Expr {
+        InitializedLeaf::Variable(InitializedVariable::new(
+            UninitializedVariable::new(format!("var_{index}")),
+            index,
+        ))
+        .to_expr()
+    }
+
+    #[test]
+    fn test_evaluate_let_binding() {
+        // let y = x * x in y + y; x = 3 binds y = 9, so the result is 18.
+        let bound_y = InitializedLeaf::Bound(1).to_expr();
+        let body = BinaryNode::Add(Box::new(bound_y.clone()), Box::new(bound_y)).to_expr(true);
+        let value = BinaryNode::Multiply(Box::new(var(0)), Box::new(var(0))).to_expr(true);
+        let expr = InitializedExpr::let_binding(1, value, body);
+
+        assert_eq!(expr.evaluate(&vec![3.0]), 18.0);
+    }
+
+    #[test]
+    fn test_evaluate_nested_let_binding() {
+        // let a = 1 in let b = a + 1 in a + b; a = 1, b = 2, result = 3.
+        let bound_a = InitializedLeaf::Bound(0).to_expr();
+        let bound_b = InitializedLeaf::Bound(1).to_expr();
+        let inner_body =
+            BinaryNode::Add(Box::new(bound_a.clone()), Box::new(bound_b)).to_expr(true);
+        let inner_value =
+            BinaryNode::Add(Box::new(bound_a), Box::new(InitializedLeaf::Constant(1.0).to_expr()))
+                .to_expr(true);
+        let inner_let = InitializedExpr::let_binding(1, inner_value, inner_body);
+        let expr = InitializedExpr::let_binding(
+            0,
+            InitializedLeaf::Constant(1.0).to_expr(),
+            inner_let.to_expr(),
+        );
+
+        assert_eq!(expr.evaluate(&vec![]), 3.0);
+    }
+}
@@ -1,10 +1,20 @@
 use crate::traits::expression::Expression;
+#[cfg(feature = "jit")]
 use crate::traits::expression_compiler::ExpressionCompiler;
+#[cfg(feature = "jit")]
 use cranelift_codegen::ir::{types, MemFlags};
+#[cfg(feature = "jit")]
 use cranelift_codegen::ir::{InstBuilder, Value};
-use cranelift_frontend::FunctionBuilder;
+#[cfg(feature = "jit")]
+use cranelift_frontend::{FunctionBuilder, Variable as CraneliftVariable};
 
+#[cfg(feature = "jit")]
+use crate::enums::scalar_width::ScalarWidth;
 use crate::structs::initialized_variable::InitializedVariable;
+#[cfg(feature = "jit")]
+use crate::structs::libm_imports::LibmImports;
+#[cfg(feature = "gpu")]
+use crate::traits::expression_shader_compiler::ExpressionShaderCompiler;
 
 use super::expr::Expr;
 use super::initialized_expr::InitializedExpr;
@@ -16,6 +26,11 @@ use super::initialized_expr::InitializedExpr;
 pub enum InitializedLeaf {
     Constant(f64),                 // Leaf node: a constant value.
     Variable(InitializedVariable), // The usize is the index of the variable in the input vector.
+
+    /// A reference to a name bound by an enclosing `InitializedExpr::Let`, by its scope slot.
+    /// Unlike `Variable`, this is read out of the JIT's local scope rather than the `*const f64`
+    /// arguments pointer.
+    Bound(usize),
 }
 
 impl InitializedLeaf {
@@ -26,32 +41,83 @@ impl InitializedLeaf {
     }
 }
 
+#[cfg(feature = "jit")]
 impl ExpressionCompiler for InitializedLeaf {
-    fn build_jit_nd(&self, builder: &mut FunctionBuilder, parameters: &[Value]) -> Value {
+    fn build_jit_nd(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        _libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value {
         match self {
-            InitializedLeaf::Constant(value) => builder.ins().f64const(*value),
+            InitializedLeaf::Constant(value) => width.const_value(builder, *value),
             InitializedLeaf::Variable(initialized_variable) => {
-                let args_ptr = parameters[0]; // *const f64
+                let args_ptr = parameters[0]; // *const {f32, f64}
 
                 // We want to load the i-th argument (0-based index).
-                let arg_offset = (initialized_variable.index() * 8) as i32; // Each f64 is 8 bytes
+                let arg_offset = (initialized_variable.index() as i32) * width.bytes();
 
                 // Load the i-th argument from the arguments pointer.
                 builder
                     .ins()
-                    .load(types::F64, MemFlags::new(), args_ptr, arg_offset)
+                    .load(width.cranelift_type(), MemFlags::new(), args_ptr, arg_offset)
+            }
+            InitializedLeaf::Bound(name_index) => {
+                builder.use_var(CraneliftVariable::new(*name_index))
+            }
+        }
+    }
+
+    fn build_jit_nd_vec(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        _libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value {
+        match self {
+            InitializedLeaf::Constant(value) => {
+                let scalar = builder.ins().f64const(*value);
+                builder.ins().splat(lanes, scalar)
+            }
+            InitializedLeaf::Variable(initialized_variable) => {
+                let args_ptr = parameters[0]; // *const f64
+
+                // The caller lays out variable `i`'s values for every lane contiguously, so the
+                // i-th variable's chunk starts `i` vectors in.
+                let arg_offset = (initialized_variable.index() * lanes.bytes() as usize) as i32;
+
+                builder.ins().load(lanes, MemFlags::new(), args_ptr, arg_offset)
             }
+            InitializedLeaf::Bound(_) => unimplemented!(
+                "let-bound names are only supported through the scalar build_jit_nd path for now"
+            ),
         }
     }
 
-    fn build_jit_1d(&self, builder: &mut FunctionBuilder, parameter: Value) -> Value {
+    fn build_jit_1d(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameter: Value,
+        _libm: &LibmImports,
+    ) -> Value {
         match self {
             InitializedLeaf::Constant(value) => builder.ins().f64const(*value),
             InitializedLeaf::Variable(_) => parameter,
+            InitializedLeaf::Bound(name_index) => {
+                builder.use_var(CraneliftVariable::new(*name_index))
+            }
         }
     }
 
-    fn build_jit_2d(&self, builder: &mut FunctionBuilder, param_0: Value, param_1: Value) -> Value {
+    fn build_jit_2d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        _libm: &LibmImports,
+    ) -> Value {
         match self {
             InitializedLeaf::Constant(value) => builder.ins().f64const(*value),
             InitializedLeaf::Variable(idx) => match idx.index() {
@@ -62,6 +128,9 @@ impl ExpressionCompiler for InitializedLeaf {
                     x
                 ),
             },
+            InitializedLeaf::Bound(name_index) => {
+                builder.use_var(CraneliftVariable::new(*name_index))
+            }
         }
     }
 
@@ -71,6 +140,7 @@ impl ExpressionCompiler for InitializedLeaf {
         param_0: Value,
         param_1: Value,
         param_2: Value,
+        _libm: &LibmImports,
     ) -> Value {
         match self {
             InitializedLeaf::Constant(value) => builder.ins().f64const(*value),
@@ -83,6 +153,34 @@ impl ExpressionCompiler for InitializedLeaf {
                     x
                 ),
             },
+            InitializedLeaf::Bound(name_index) => {
+                builder.use_var(CraneliftVariable::new(*name_index))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl ExpressionShaderCompiler for InitializedLeaf {
+    fn wgsl_expr(&self, buffer: &str) -> String {
+        match self {
+            // WGSL's untyped numeric literals default to `AbstractInt` unless they carry a
+            // decimal point, so `{value:?}` (always printing one, e.g. `2.0`) is used rather than
+            // `{value}`.
+            InitializedLeaf::Constant(value) => format!("{value:?}"),
+            InitializedLeaf::Variable(initialized_variable) => {
+                format!(
+                    "{buffer}[row * NUM_VARIABLES + {}u]",
+                    initialized_variable.index()
+                )
+            }
+            InitializedLeaf::Bound(name_index) => {
+                unimplemented!(
+                    "bound name {} can only be resolved by the JIT path; \
+                     the shader compiler doesn't have a local scope yet",
+                    name_index
+                )
+            }
         }
     }
 }
@@ -92,6 +190,11 @@ impl Expression for InitializedLeaf {
         match self {
             InitializedLeaf::Constant(value) => *value,
             InitializedLeaf::Variable(variable) => variables[variable.index()],
+
+            // The enclosing `InitializedExpr::Let` writes the bound value into this slot of a
+            // scoped copy of `variables` before evaluating its body, so a plain index read is
+            // all that's needed here - same as `Variable` above.
+            InitializedLeaf::Bound(name_index) => variables[*name_index],
         }
     }
 
@@ -99,6 +202,7 @@ impl Expression for InitializedLeaf {
         match self {
             InitializedLeaf::Constant(_) => 0,
             InitializedLeaf::Variable(idx) => idx.index() + 1,
+            InitializedLeaf::Bound(name_index) => *name_index + 1,
         }
     }
 }
@@ -109,6 +213,7 @@ mod tests {
 
     use super::*;
 
+    #[cfg(feature = "jit")]
     #[test]
     fn test_expression_1_variable() {
         let f = InitializedLeaf::Variable(InitializedVariable::new(
@@ -123,16 +228,17 @@ mod tests {
         let values_2 = vec![2.0];
         let values_3 = vec![3.0];
 
-        assert_eq!(f(values_1.as_ptr(), values_1.len()), 1.0);
-        assert_eq!(f(values_2.as_ptr(), values_2.len()), 2.0);
-        assert_eq!(f(values_3.as_ptr(), values_3.len()), 3.0);
+        assert_eq!(f.call(values_1.as_ptr(), values_1.len()), 1.0);
+        assert_eq!(f.call(values_2.as_ptr(), values_2.len()), 2.0);
+        assert_eq!(f.call(values_3.as_ptr(), values_3.len()), 3.0);
     }
 
+    #[cfg(feature = "jit")]
     #[test]
     fn test_expression_constant() {
         let f = InitializedLeaf::Constant(2.0).compile_nd().unwrap();
         let values = vec![];
-        assert_eq!(f(values.as_ptr(), values.len()), 2.0);
+        assert_eq!(f.call(values.as_ptr(), values.len()), 2.0);
     }
 
     #[test]
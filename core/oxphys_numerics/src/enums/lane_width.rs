@@ -0,0 +1,50 @@
+#![cfg(feature = "jit")]
+
+use cranelift_codegen::ir::types;
+
+/// # LaneWidth
+/// The number of `f64` lanes a vectorized kernel processes per call. Exposed on the
+/// `compile_nd_vec`/`evaluate_vec` family so callers can pick a width that matches the target
+/// ISA's native vector registers (SSE2-class hardware tops out at two lanes, AVX-class hardware
+/// can do four). Gated behind the `jit` feature, since its only consumer is the JIT vector kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LaneWidth {
+    /// Process two rows per call, using Cranelift's `F64X2` vector type.
+    #[default]
+    Two,
+
+    /// Process four rows per call, using Cranelift's `F64X4` vector type.
+    Four,
+}
+
+impl LaneWidth {
+    /// # Lanes
+    /// The number of `f64` lanes this width packs into one vector register.
+    pub fn lanes(&self) -> usize {
+        match self {
+            LaneWidth::Two => 2,
+            LaneWidth::Four => 4,
+        }
+    }
+
+    /// # Cranelift type
+    /// The Cranelift vector type that backs this lane width.
+    pub(crate) fn cranelift_type(&self) -> types::Type {
+        match self {
+            LaneWidth::Two => types::F64X2,
+            LaneWidth::Four => types::F64X4,
+        }
+    }
+}
+
+impl TryFrom<usize> for LaneWidth {
+    type Error = ();
+
+    fn try_from(lanes: usize) -> Result<Self, Self::Error> {
+        match lanes {
+            2 => Ok(LaneWidth::Two),
+            4 => Ok(LaneWidth::Four),
+            _ => Err(()),
+        }
+    }
+}
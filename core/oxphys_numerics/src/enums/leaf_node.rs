@@ -1,3 +1,5 @@
+use crate::enums::scalar_width::ScalarWidth;
+use crate::structs::libm_imports::LibmImports;
 use crate::traits::expression::Expression;
 use crate::traits::expression_compiler::ExpressionCompiler;
 use cranelift_codegen::ir::{types, MemFlags};
@@ -16,32 +18,73 @@ pub enum LeafNode {
 }
 
 impl ExpressionCompiler for LeafNode {
-    fn build_jit_nd(&self, builder: &mut FunctionBuilder, parameters: &[Value]) -> Value {
+    fn build_jit_nd(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        _libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value {
         match self {
-            LeafNode::Constant(value) => builder.ins().f64const(*value),
+            LeafNode::Constant(value) => width.const_value(builder, *value),
             LeafNode::Variable(idx) => {
-                let args_ptr = parameters[0]; // *const f64
+                let args_ptr = parameters[0]; // *const {f32, f64}
 
                 // We want to load the i-th argument (0-based index).
                 let i = *idx;
-                let arg_offset = (i * 8) as i32; // Each f64 is 8 bytes
+                let arg_offset = (i as i32) * width.bytes();
 
                 // Load the i-th argument from the arguments pointer.
                 builder
                     .ins()
-                    .load(types::F64, MemFlags::new(), args_ptr, arg_offset)
+                    .load(width.cranelift_type(), MemFlags::new(), args_ptr, arg_offset)
+            }
+        }
+    }
+
+    fn build_jit_nd_vec(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        _libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value {
+        match self {
+            LeafNode::Constant(value) => {
+                let scalar = builder.ins().f64const(*value);
+                builder.ins().splat(lanes, scalar)
+            }
+            LeafNode::Variable(idx) => {
+                let args_ptr = parameters[0]; // *const f64
+
+                // The caller lays out variable `i`'s values for every lane contiguously, so the
+                // i-th variable's chunk starts `i` vectors in.
+                let arg_offset = (*idx * lanes.bytes() as usize) as i32;
+
+                builder.ins().load(lanes, MemFlags::new(), args_ptr, arg_offset)
             }
         }
     }
 
-    fn build_jit_1d(&self, builder: &mut FunctionBuilder, parameter: Value) -> Value {
+    fn build_jit_1d(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameter: Value,
+        _libm: &LibmImports,
+    ) -> Value {
         match self {
             LeafNode::Constant(value) => builder.ins().f64const(*value),
             LeafNode::Variable(_) => parameter,
         }
     }
 
-    fn build_jit_2d(&self, builder: &mut FunctionBuilder, param_0: Value, param_1: Value) -> Value {
+    fn build_jit_2d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        _libm: &LibmImports,
+    ) -> Value {
         match self {
             LeafNode::Constant(value) => builder.ins().f64const(*value),
             LeafNode::Variable(idx) => match idx {
@@ -61,6 +104,7 @@ impl ExpressionCompiler for LeafNode {
         param_0: Value,
         param_1: Value,
         param_2: Value,
+        _libm: &LibmImports,
     ) -> Value {
         match self {
             LeafNode::Constant(value) => builder.ins().f64const(*value),
@@ -105,16 +149,16 @@ mod tests {
         let values_2 = vec![2.0];
         let values_3 = vec![3.0];
 
-        assert_eq!(f(values_1.as_ptr(), values_1.len()), 1.0);
-        assert_eq!(f(values_2.as_ptr(), values_2.len()), 2.0);
-        assert_eq!(f(values_3.as_ptr(), values_3.len()), 3.0);
+        assert_eq!(f.call(values_1.as_ptr(), values_1.len()), 1.0);
+        assert_eq!(f.call(values_2.as_ptr(), values_2.len()), 2.0);
+        assert_eq!(f.call(values_3.as_ptr(), values_3.len()), 3.0);
     }
 
     #[test]
     fn test_expression_constant() {
         let f = LeafNode::Constant(2.0).compile_nd().unwrap();
         let values = vec![];
-        assert_eq!(f(values.as_ptr(), values.len()), 2.0);
+        assert_eq!(f.call(values.as_ptr(), values.len()), 2.0);
     }
 
     #[test]
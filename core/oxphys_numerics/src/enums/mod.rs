@@ -1,9 +1,14 @@
 pub mod binary_node;
+pub mod comparison_node;
 pub mod expr;
 pub mod initialized_expression;
 pub mod initialized_leaf;
+pub mod lane_width;
 pub mod leaf_node;
+pub mod opt_level;
 pub mod scalar_type;
+pub mod scalar_width;
+pub mod ternary_node;
 pub mod unary_node;
 pub mod uninitialized_expression;
 pub mod uninitialized_leaf;
@@ -0,0 +1,29 @@
+/// # OptLevel
+/// The optimization level to compile an expression with. Exposed on the `compile_*` family of
+/// methods so callers can trade compile time for runtime speed, independent of which
+/// [`crate::backend::Backend`] does the actual codegen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OptLevel {
+    /// Do the minimum amount of optimization, favouring fast compilation.
+    None,
+
+    /// Optimize aggressively for runtime speed. This is the default, since `oxphys_numerics`
+    /// expressions are typically compiled once and then called many times.
+    #[default]
+    Speed,
+
+    /// Optimize for runtime speed while also keeping generated code small.
+    SpeedAndSize,
+}
+
+impl OptLevel {
+    /// # Cranelift setting
+    /// The `cranelift_codegen::settings` string value for this optimization level.
+    pub(crate) fn cranelift_setting(&self) -> &'static str {
+        match self {
+            OptLevel::None => "none",
+            OptLevel::Speed => "speed",
+            OptLevel::SpeedAndSize => "speed_and_size",
+        }
+    }
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "jit")]
+
+use cranelift_codegen::ir::{types, InstBuilder, Value};
+use cranelift_frontend::FunctionBuilder;
+
+/// # ScalarWidth
+/// The floating-point precision a JIT-compiled scalar kernel computes in. Exposed on
+/// [`crate::traits::expression_compiler::ExpressionCompiler::build_jit_nd`] so the same recursive
+/// codegen routine can emit either precision: `F32` packs more values per cache line and per SSE/
+/// AVX register than `F64`, which matters most once `evaluate_vec` is streaming a large array
+/// through the kernel. Gated behind the `jit` feature, since its only consumer is the JIT kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalarWidth {
+    /// Compute in `f32`, Cranelift's `F32` type.
+    F32,
+
+    /// Compute in `f64`, Cranelift's `F64` type.
+    #[default]
+    F64,
+}
+
+impl ScalarWidth {
+    /// # Cranelift type
+    /// The Cranelift scalar type that backs this width.
+    pub(crate) fn cranelift_type(&self) -> types::Type {
+        match self {
+            ScalarWidth::F32 => types::F32,
+            ScalarWidth::F64 => types::F64,
+        }
+    }
+
+    /// # Bytes
+    /// The size, in bytes, of one element at this width - the stride a leaf's `Variable` load
+    /// uses when indexing into the variables array.
+    pub(crate) fn bytes(&self) -> i32 {
+        match self {
+            ScalarWidth::F32 => 4,
+            ScalarWidth::F64 => 8,
+        }
+    }
+
+    /// # Const value
+    /// Emit `value` as an immediate of this width: `f32const` after a narrowing cast for `F32`,
+    /// `f64const` unchanged for `F64`.
+    pub(crate) fn const_value(&self, builder: &mut FunctionBuilder, value: f64) -> Value {
+        match self {
+            ScalarWidth::F32 => builder.ins().f32const(value as f32),
+            ScalarWidth::F64 => builder.ins().f64const(value),
+        }
+    }
+
+    /// # Widen to F64
+    /// Promote `value` to `f64` if it's currently `f32`, otherwise return it unchanged. Used
+    /// wherever codegen needs to reach a `libm` call or one of the
+    /// [`transcendental_kernels`](crate::structs::transcendental_kernels) - both are hard-coded
+    /// against `f64`, since narrowing their range reduction to bit-exact `f32` versions is a
+    /// separate, bigger change than this width parameter covers on its own.
+    pub(crate) fn widen_to_f64(&self, builder: &mut FunctionBuilder, value: Value) -> Value {
+        match self {
+            ScalarWidth::F32 => builder.ins().fpromote(types::F64, value),
+            ScalarWidth::F64 => value,
+        }
+    }
+
+    /// # Narrow from F64
+    /// The inverse of [`widen_to_f64`](Self::widen_to_f64): demote an `f64` result back down to
+    /// this width if it's `F32`, otherwise return it unchanged.
+    pub(crate) fn narrow_from_f64(&self, builder: &mut FunctionBuilder, value: Value) -> Value {
+        match self {
+            ScalarWidth::F32 => builder.ins().fdemote(types::F32, value),
+            ScalarWidth::F64 => value,
+        }
+    }
+}
@@ -0,0 +1,239 @@
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::condcodes::FloatCC;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::types;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::{InstBuilder, Value};
+#[cfg(feature = "jit")]
+use cranelift_frontend::FunctionBuilder;
+
+#[cfg(feature = "jit")]
+use crate::enums::scalar_width::ScalarWidth;
+#[cfg(feature = "jit")]
+use crate::structs::libm_imports::LibmImports;
+#[cfg(feature = "jit")]
+use crate::traits::expression_compiler::ExpressionCompiler;
+#[cfg(feature = "gpu")]
+use crate::traits::expression_shader_compiler::ExpressionShaderCompiler;
+use crate::traits::{expression::Expression, expression_node::ExpressionNode};
+
+use super::{expr::Expr, initialized_expr::InitializedExpr, uninitialized_expr::UninitializedExpr};
+
+/// # TernaryNode
+/// A node with exactly three child expressions. `Select` is the only variant today; it's kept as
+/// its own (currently single-variant) node type, mirroring how `BinaryNode`/`UnaryNode` are split
+/// out by arity, so adding a second ternary operator later doesn't require threading a fourth
+/// child arity through `Expr` itself.
+#[derive(Debug, Clone)]
+pub enum TernaryNode {
+    /// `Select(cond, if_true, if_false)`: evaluates to `if_true` when `cond` is non-zero,
+    /// `if_false` otherwise. Lowers through `builder.ins().select(...)`, so both branches are
+    /// always computed and no control flow is emitted - the branchless-conditional pattern other
+    /// compilers use for this kind of ternary.
+    Select(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+impl TernaryNode {
+    /// # Cond
+    /// Get the condition expression.
+    fn cond(&self) -> &Expr {
+        match self {
+            TernaryNode::Select(cond, _, _) => cond,
+        }
+    }
+
+    /// # If true
+    /// Get the expression to evaluate to when the condition holds.
+    fn if_true(&self) -> &Expr {
+        match self {
+            TernaryNode::Select(_, if_true, _) => if_true,
+        }
+    }
+
+    /// # If false
+    /// Get the expression to evaluate to when the condition doesn't hold.
+    fn if_false(&self) -> &Expr {
+        match self {
+            TernaryNode::Select(_, _, if_false) => if_false,
+        }
+    }
+}
+
+#[cfg(feature = "jit")]
+impl TernaryNode {
+    /// # To condition
+    /// Cranelift's `select` needs a native boolean, but `cond` is a regular `f64`-valued `Expr`
+    /// (e.g. the `1.0`/`0.0` a `ComparisonNode` produces); treat any non-zero value as true via
+    /// `fcmp ne 0.0`, the same "truthy float" rule C's ternary operator uses. Shared with
+    /// `crate::dag::ExprDag::build_jit_nd`, which applies the same conversion once a `Select` has
+    /// been hash-consed into the DAG (always at `f64` width, since the DAG path doesn't go
+    /// through `ScalarWidth` yet).
+    pub(crate) fn to_condition(
+        builder: &mut FunctionBuilder,
+        cond_value: Value,
+        width: ScalarWidth,
+    ) -> Value {
+        let zero = width.const_value(builder, 0.0);
+        builder.ins().fcmp(FloatCC::NotEqual, cond_value, zero)
+    }
+}
+
+impl ExpressionNode for TernaryNode {
+    fn to_expr(&self, is_initialized: bool) -> Expr {
+        match is_initialized {
+            true => Expr::Initialized(InitializedExpr::Ternary(self.clone())),
+            false => Expr::Uninitialized(UninitializedExpr::Ternary(self.clone())),
+        }
+    }
+}
+
+#[cfg(feature = "jit")]
+impl ExpressionCompiler for TernaryNode {
+    fn build_jit_nd(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value {
+        let cond_value = self.cond().build_jit_nd(builder, parameters, libm, width);
+        let if_true_value = self.if_true().build_jit_nd(builder, parameters, libm, width);
+        let if_false_value = self
+            .if_false()
+            .build_jit_nd(builder, parameters, libm, width);
+        let condition = Self::to_condition(builder, cond_value, width);
+        builder
+            .ins()
+            .select(condition, if_true_value, if_false_value)
+    }
+
+    fn build_jit_nd_vec(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value {
+        // `select`/`fcmp` only operate on scalars, and there's no vector form of either plumbed
+        // through yet, so fall back to a lane-wise select like the transcendental unary ops.
+        let cond_value = self.cond().build_jit_nd_vec(builder, parameters, libm, lanes);
+        let if_true_value = self
+            .if_true()
+            .build_jit_nd_vec(builder, parameters, libm, lanes);
+        let if_false_value = self
+            .if_false()
+            .build_jit_nd_vec(builder, parameters, libm, lanes);
+
+        let lane_count = lanes.lane_count();
+        let zero = builder.ins().f64const(0.0);
+        let mut result = builder.ins().splat(lanes, zero);
+        for lane in 0..lane_count {
+            let cond_scalar = builder.ins().extractlane(cond_value, lane as u8);
+            let if_true_scalar = builder.ins().extractlane(if_true_value, lane as u8);
+            let if_false_scalar = builder.ins().extractlane(if_false_value, lane as u8);
+            let condition = Self::to_condition(builder, cond_scalar, ScalarWidth::F64);
+            let scalar_result = builder
+                .ins()
+                .select(condition, if_true_scalar, if_false_scalar);
+            result = builder.ins().insertlane(result, scalar_result, lane as u8);
+        }
+        result
+    }
+
+    fn build_jit_1d(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameter: Value,
+        libm: &LibmImports,
+    ) -> Value {
+        let cond_value = self.cond().build_jit_1d(builder, parameter, libm);
+        let if_true_value = self.if_true().build_jit_1d(builder, parameter, libm);
+        let if_false_value = self.if_false().build_jit_1d(builder, parameter, libm);
+        let condition = Self::to_condition(builder, cond_value, ScalarWidth::F64);
+        builder
+            .ins()
+            .select(condition, if_true_value, if_false_value)
+    }
+
+    fn build_jit_2d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        libm: &LibmImports,
+    ) -> Value {
+        let cond_value = self.cond().build_jit_2d(builder, param_0, param_1, libm);
+        let if_true_value = self.if_true().build_jit_2d(builder, param_0, param_1, libm);
+        let if_false_value = self.if_false().build_jit_2d(builder, param_0, param_1, libm);
+        let condition = Self::to_condition(builder, cond_value, ScalarWidth::F64);
+        builder
+            .ins()
+            .select(condition, if_true_value, if_false_value)
+    }
+
+    fn build_jit_3d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        param_2: Value,
+        libm: &LibmImports,
+    ) -> Value {
+        let cond_value = self
+            .cond()
+            .build_jit_3d(builder, param_0, param_1, param_2, libm);
+        let if_true_value = self
+            .if_true()
+            .build_jit_3d(builder, param_0, param_1, param_2, libm);
+        let if_false_value = self
+            .if_false()
+            .build_jit_3d(builder, param_0, param_1, param_2, libm);
+        let condition = Self::to_condition(builder, cond_value, ScalarWidth::F64);
+        builder
+            .ins()
+            .select(condition, if_true_value, if_false_value)
+    }
+
+    fn contains_let(&self) -> bool {
+        self.cond().contains_let()
+            || self.if_true().contains_let()
+            || self.if_false().contains_let()
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl ExpressionShaderCompiler for TernaryNode {
+    fn wgsl_expr(&self, buffer: &str) -> String {
+        match self {
+            TernaryNode::Select(_, _, _) => {
+                let cond = self.cond().wgsl_expr(buffer);
+                let if_true = self.if_true().wgsl_expr(buffer);
+                let if_false = self.if_false().wgsl_expr(buffer);
+                // Same "truthy float" rule as `TernaryNode::to_condition` on the JIT path: any
+                // non-zero `cond` selects `if_true`.
+                format!("select({if_false}, {if_true}, {cond} != 0.0)")
+            }
+        }
+    }
+}
+
+impl Expression for TernaryNode {
+    fn evaluate(&self, variables: &Vec<f64>) -> f64 {
+        match self {
+            TernaryNode::Select(cond, if_true, if_false) => {
+                if cond.evaluate(variables) != 0.0 {
+                    if_true.evaluate(variables)
+                } else {
+                    if_false.evaluate(variables)
+                }
+            }
+        }
+    }
+
+    fn num_variables(&self) -> usize {
+        self.cond()
+            .num_variables()
+            .max(self.if_true().num_variables())
+            .max(self.if_false().num_variables())
+    }
+}
@@ -1,8 +1,24 @@
 use crate::traits::expression::Expression;
-use crate::traits::expression_compiler::ExpressionCompiler;
 use crate::traits::expression_node::ExpressionNode;
+#[cfg(feature = "jit")]
+use crate::enums::scalar_width::ScalarWidth;
+#[cfg(feature = "jit")]
+use crate::structs::libm_imports::LibmImports;
+#[cfg(feature = "jit")]
+use crate::structs::transcendental_kernels;
+#[cfg(feature = "jit")]
+use crate::traits::expression_compiler::ExpressionCompiler;
+#[cfg(feature = "gpu")]
+use crate::traits::expression_shader_compiler::ExpressionShaderCompiler;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::types;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::FuncRef;
+#[cfg(feature = "jit")]
 use cranelift_codegen::ir::InstBuilder;
+#[cfg(feature = "jit")]
 use cranelift_codegen::ir::Value;
+#[cfg(feature = "jit")]
 use cranelift_frontend::FunctionBuilder;
 
 use super::expr::Expr;
@@ -35,13 +51,89 @@ impl UnaryNode {
         }
     }
 
-    fn expression_value(&self, builder: &mut FunctionBuilder<'_>, input: Value) -> Value {
+}
+
+#[cfg(feature = "jit")]
+impl UnaryNode {
+    /// # Expression value
+    /// Compute this unary operation's value, given its already-built `input`. Shared across every
+    /// scalar arity (`1d`/`2d`/`3d`/`nd`), none of which has a narrower way to reach the
+    /// transcendentals. `Sin`/`Cos`/`Exp`/`Ln` are built in-IR via
+    /// [`transcendental_kernels`](crate::structs::transcendental_kernels) rather than a `libm`
+    /// call, so `libm` is unused here; it's threaded through for callers that build a whole tree
+    /// with one shared `libm` handle, which `BinaryNode::Pow`/`Log` and the vector path
+    /// (`expression_value_nd_vec`) still call into. The kernels are
+    /// hard-coded against `f64`'s bit layout, so at `width == F32` `input` is widened before the
+    /// call and the result narrowed back afterwards.
+    fn expression_value(
+        &self,
+        builder: &mut FunctionBuilder<'_>,
+        input: Value,
+        _libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value {
         match self {
             UnaryNode::Negate(_) => builder.ins().fneg(input),
             UnaryNode::Sqrt(_) => builder.ins().sqrt(input),
-            _ => unimplemented!(),
+            UnaryNode::Sin(_) | UnaryNode::Cos(_) | UnaryNode::Exp(_) | UnaryNode::Ln(_) => {
+                let wide_input = width.widen_to_f64(builder, input);
+                let wide_result = match self {
+                    UnaryNode::Sin(_) => transcendental_kernels::build_sin(builder, wide_input),
+                    UnaryNode::Cos(_) => transcendental_kernels::build_cos(builder, wide_input),
+                    UnaryNode::Exp(_) => transcendental_kernels::build_exp(builder, wide_input),
+                    UnaryNode::Ln(_) => transcendental_kernels::build_ln(builder, wide_input),
+                    UnaryNode::Negate(_) | UnaryNode::Sqrt(_) => unreachable!(),
+                };
+                width.narrow_from_f64(builder, wide_result)
+            }
         }
     }
+
+    /// # Expression value ND vec
+    /// Like `expression_value`, but `input` is a vector of `lanes` lanes. `fneg`/`sqrt` have
+    /// vector forms that operate lane-wise directly, but libm has no vectorized `sin`/`cos`/
+    /// `exp`/`log`, so those fall back to a lane-wise libcall: extract each lane, call the scalar
+    /// function, and insert the result back into the output vector.
+    fn expression_value_nd_vec(
+        &self,
+        builder: &mut FunctionBuilder<'_>,
+        input: Value,
+        libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value {
+        match self {
+            UnaryNode::Negate(_) => builder.ins().fneg(input),
+            UnaryNode::Sqrt(_) => builder.ins().sqrt(input),
+            UnaryNode::Sin(_) => self.lane_wise_libcall(builder, input, libm.sin, lanes),
+            UnaryNode::Cos(_) => self.lane_wise_libcall(builder, input, libm.cos, lanes),
+            UnaryNode::Exp(_) => self.lane_wise_libcall(builder, input, libm.exp, lanes),
+            UnaryNode::Ln(_) => self.lane_wise_libcall(builder, input, libm.ln, lanes),
+        }
+    }
+
+    /// # Lane wise libcall
+    /// Apply the scalar libm function `func` to every lane of `input` independently, returning a
+    /// vector of the same width built back up lane by lane.
+    fn lane_wise_libcall(
+        &self,
+        builder: &mut FunctionBuilder<'_>,
+        input: Value,
+        func: FuncRef,
+        lanes: types::Type,
+    ) -> Value {
+        let lane_count = lanes.lane_count();
+        let zero = builder.ins().f64const(0.0);
+        let mut result = builder.ins().splat(lanes, zero);
+
+        for lane in 0..lane_count {
+            let scalar = builder.ins().extractlane(input, lane as u8);
+            let call = builder.ins().call(func, &[scalar]);
+            let scalar_result = builder.inst_results(call)[0];
+            result = builder.ins().insertlane(result, scalar_result, lane as u8);
+        }
+
+        result
+    }
 }
 
 impl ExpressionNode for UnaryNode {
@@ -53,23 +145,55 @@ impl ExpressionNode for UnaryNode {
     }
 }
 
+#[cfg(feature = "jit")]
 impl ExpressionCompiler for UnaryNode {
-    fn build_jit_nd(&self, builder: &mut FunctionBuilder, parameters: &[Value]) -> Value {
+    fn build_jit_nd(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value {
         // Start by building the inner expression, then apply the unary operation.
-        let input = self.inner().build_jit_nd(builder, parameters);
-        self.expression_value(builder, input)
+        let input = self.inner().build_jit_nd(builder, parameters, libm, width);
+        self.expression_value(builder, input, libm, width)
     }
 
-    fn build_jit_1d(&self, builder: &mut FunctionBuilder, parameter: Value) -> Value {
+    fn build_jit_nd_vec(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value {
         // Start by building the inner expression, then apply the unary operation.
-        let input = self.inner().build_jit_1d(builder, parameter);
-        self.expression_value(builder, input)
+        let input = self
+            .inner()
+            .build_jit_nd_vec(builder, parameters, libm, lanes);
+        self.expression_value_nd_vec(builder, input, libm, lanes)
     }
 
-    fn build_jit_2d(&self, builder: &mut FunctionBuilder, param_0: Value, param_1: Value) -> Value {
+    fn build_jit_1d(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameter: Value,
+        libm: &LibmImports,
+    ) -> Value {
         // Start by building the inner expression, then apply the unary operation.
-        let input = self.inner().build_jit_2d(builder, param_0, param_1);
-        self.expression_value(builder, input)
+        let input = self.inner().build_jit_1d(builder, parameter, libm);
+        self.expression_value(builder, input, libm, ScalarWidth::F64)
+    }
+
+    fn build_jit_2d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        libm: &LibmImports,
+    ) -> Value {
+        // Start by building the inner expression, then apply the unary operation.
+        let input = self.inner().build_jit_2d(builder, param_0, param_1, libm);
+        self.expression_value(builder, input, libm, ScalarWidth::F64)
     }
 
     fn build_jit_3d(
@@ -78,12 +202,33 @@ impl ExpressionCompiler for UnaryNode {
         param_0: Value,
         param_1: Value,
         param_2: Value,
+        libm: &LibmImports,
     ) -> Value {
         // Start by building the inner expression, then apply the unary operation.
         let input = self
             .inner()
-            .build_jit_3d(builder, param_0, param_1, param_2);
-        self.expression_value(builder, input)
+            .build_jit_3d(builder, param_0, param_1, param_2, libm);
+        self.expression_value(builder, input, libm, ScalarWidth::F64)
+    }
+
+    fn contains_let(&self) -> bool {
+        self.inner().contains_let()
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl ExpressionShaderCompiler for UnaryNode {
+    fn wgsl_expr(&self, buffer: &str) -> String {
+        let input = self.inner().wgsl_expr(buffer);
+        match self {
+            UnaryNode::Negate(_) => format!("(-{input})"),
+            UnaryNode::Sqrt(_) => format!("sqrt({input})"),
+            UnaryNode::Sin(_) => format!("sin({input})"),
+            UnaryNode::Cos(_) => format!("cos({input})"),
+            UnaryNode::Exp(_) => format!("exp({input})"),
+            // WGSL's `log` is the natural logarithm (`log2` is the base-2 one), matching `Ln`.
+            UnaryNode::Ln(_) => format!("log({input})"),
+        }
     }
 }
 
@@ -107,7 +252,17 @@ impl Expression for UnaryNode {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::enums::leaf_node::LeafNode;
+    use crate::enums::initialized_leaf::InitializedLeaf;
+    use crate::structs::initialized_variable::InitializedVariable;
+    use crate::structs::uninitialized_variable::UninitializedVariable;
+
+    fn var(index: usize) -> Expr {
+        InitializedLeaf::Variable(InitializedVariable::new(
+            UninitializedVariable::new(format!("var_{index}")),
+            index,
+        ))
+        .to_expr()
+    }
 
     #[test]
     fn test_evaluate() {
@@ -115,31 +270,33 @@ mod tests {
         let variables = vec![1.0, 2.0, 3.0];
 
         // f(x) = -x
-        let expr = UnaryNode::Negate(Box::new(Expr::Initialized(LeafNode::Variable(1))));
+        let expr = UnaryNode::Negate(Box::new(var(1)));
         assert_eq!(expr.evaluate(&variables), -2.0);
     }
 
+    #[cfg(feature = "jit")]
     #[test]
     fn test_compiled_negate() {
         // Set up the variables vector.
         let variables = vec![15.0];
 
         // f(x) = -x
-        let expr = UnaryNode::Negate(Box::new(Expr::Leaf(LeafNode::Variable(0))));
+        let expr = UnaryNode::Negate(Box::new(var(0)));
         let f = expr.compile_nd().unwrap();
 
-        assert_eq!(f(variables.as_ptr(), variables.len()), -15.0);
+        assert_eq!(f.call(variables.as_ptr(), variables.len()), -15.0);
     }
 
+    #[cfg(feature = "jit")]
     #[test]
     fn test_compiled_sqrt() {
         // Set up the variables vector.
         let variables = vec![16.0];
 
         // f(x) = sqrt(x)
-        let expr = UnaryNode::Sqrt(Box::new(Expr::Leaf(LeafNode::Variable(0))));
+        let expr = UnaryNode::Sqrt(Box::new(var(0)));
         let f = expr.compile_nd().unwrap();
 
-        assert_eq!(f(variables.as_ptr(), variables.len()), 4.0);
+        assert_eq!(f.call(variables.as_ptr(), variables.len()), 4.0);
     }
 }
@@ -1,10 +1,12 @@
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
-use crate::errors::expression_error::ExpressionError;
+use crate::structs::initialized_variable::InitializedVariable;
+use crate::traits::expression_node::ExpressionNode;
 
 use super::{
-    binary_node::BinaryNode, expr::Expr, unary_node::UnaryNode,
-    uninitialized_leaf::UninitializedLeaf,
+    binary_node::BinaryNode, comparison_node::ComparisonNode, expr::Expr,
+    initialized_expr::InitializedExpr, initialized_leaf::InitializedLeaf,
+    ternary_node::TernaryNode, unary_node::UnaryNode, uninitialized_leaf::UninitializedLeaf,
 };
 
 /// # UninitializedExpr
@@ -15,13 +17,30 @@ pub enum UninitializedExpr {
     Leaf(UninitializedLeaf),
     Unary(UnaryNode),
     Binary(BinaryNode),
+    Comparison(ComparisonNode),
+    Ternary(TernaryNode),
+
+    /// A let-binding: `name` is looked up by, well, name, just like a free variable - the two
+    /// only diverge once `initialize` assigns indices, at which point a reference to `name`
+    /// inside `body` becomes `InitializedLeaf::Bound` instead of `InitializedLeaf::Variable`.
+    /// This is what lets let-bound names and free variables share one coherent index space.
+    Let {
+        name: String,
+        value: Box<Expr>,
+        body: Box<Expr>,
+    },
 }
 
 impl UninitializedExpr {
     /// # Initialize
-    /// Returns an initialized Expr struct.
-    pub fn initialize(self, index: usize) -> Result<Expr, ExpressionError> {
-        unimplemented!()
+    /// Resolve every free variable name in this tree to an index, turning it into an [`Expr`]
+    /// ready for `evaluate`/`compile_nd`. `variables` is the growing, order-preserving registry of
+    /// free variable names seen so far (across the whole parse, not just this subtree): a name
+    /// already present keeps its existing index, and a fresh name is appended and given the next
+    /// one - the same "dedupe by first-seen order" rule
+    /// [`crate::structs::variable::Variable::initialize`] uses.
+    pub fn initialize(self, variables: &mut Vec<String>) -> Expr {
+        initialize_scoped(self, variables, &mut Vec::new())
     }
 
     /// # To expr
@@ -82,3 +101,138 @@ impl Neg for UninitializedExpr {
         UninitializedExpr::Unary(UnaryNode::Negate(Box::new(self.to_expr())))
     }
 }
+
+/// # Resolve index
+/// Look `name` up in `variables`, assigning it the next free index the first time it's seen.
+fn resolve_index(variables: &mut Vec<String>, name: &str) -> usize {
+    match variables.iter().position(|existing| existing == name) {
+        Some(index) => index,
+        None => {
+            variables.push(name.to_string());
+            variables.len() - 1
+        }
+    }
+}
+
+/// # Initialize scoped
+/// Recursive worker behind `initialize`. `bound` is the stack of let-bound names currently in
+/// scope, innermost last, so a `let x = ... in ...` shadows an outer binding of the same name
+/// for the duration of its body.
+fn initialize_scoped(
+    expr: UninitializedExpr,
+    variables: &mut Vec<String>,
+    bound: &mut Vec<(String, usize)>,
+) -> Expr {
+    match expr {
+        UninitializedExpr::Leaf(UninitializedLeaf::Constant(value)) => {
+            InitializedLeaf::Constant(value).to_expr()
+        }
+        UninitializedExpr::Leaf(UninitializedLeaf::Variable(variable)) => {
+            match bound.iter().rev().find(|(name, _)| name == variable.name()) {
+                Some((_, index)) => InitializedLeaf::Bound(*index).to_expr(),
+                None => {
+                    let index = resolve_index(variables, variable.name());
+                    InitializedLeaf::Variable(InitializedVariable::new(variable, index)).to_expr()
+                }
+            }
+        }
+        UninitializedExpr::Unary(unary) => initialize_unary(unary, variables, bound),
+        UninitializedExpr::Binary(binary) => initialize_binary(binary, variables, bound),
+        UninitializedExpr::Comparison(comparison) => {
+            initialize_comparison(comparison, variables, bound)
+        }
+        UninitializedExpr::Ternary(ternary) => initialize_ternary(ternary, variables, bound),
+        UninitializedExpr::Let { name, value, body } => {
+            let value = initialize_child(*value, variables, bound);
+            let name_index = resolve_index(variables, &name);
+            bound.push((name, name_index));
+            let body = initialize_child(*body, variables, bound);
+            bound.pop();
+            InitializedExpr::let_binding(name_index, value, body).to_expr()
+        }
+    }
+}
+
+/// # Initialize child
+/// Initialize one child `Expr`. An already-`Initialized` child (built directly through the
+/// `InitializedExpr`/`Expression` operator overloads rather than parsed from source) passes
+/// through unchanged, since there's nothing left to resolve.
+fn initialize_child(
+    expr: Expr,
+    variables: &mut Vec<String>,
+    bound: &mut Vec<(String, usize)>,
+) -> Expr {
+    match expr {
+        Expr::Initialized(_) => expr,
+        Expr::Uninitialized(inner) => initialize_scoped(inner, variables, bound),
+    }
+}
+
+fn initialize_unary(
+    unary: UnaryNode,
+    variables: &mut Vec<String>,
+    bound: &mut Vec<(String, usize)>,
+) -> Expr {
+    let (inner, rebuild): (Box<Expr>, fn(Box<Expr>) -> UnaryNode) = match unary {
+        UnaryNode::Negate(inner) => (inner, UnaryNode::Negate),
+        UnaryNode::Sqrt(inner) => (inner, UnaryNode::Sqrt),
+        UnaryNode::Sin(inner) => (inner, UnaryNode::Sin),
+        UnaryNode::Cos(inner) => (inner, UnaryNode::Cos),
+        UnaryNode::Exp(inner) => (inner, UnaryNode::Exp),
+        UnaryNode::Ln(inner) => (inner, UnaryNode::Ln),
+    };
+    let inner = initialize_child(*inner, variables, bound);
+    rebuild(Box::new(inner)).to_expr(true)
+}
+
+fn initialize_binary(
+    binary: BinaryNode,
+    variables: &mut Vec<String>,
+    bound: &mut Vec<(String, usize)>,
+) -> Expr {
+    let (left, right, rebuild): (Box<Expr>, Box<Expr>, fn(Box<Expr>, Box<Expr>) -> BinaryNode) =
+        match binary {
+            BinaryNode::Add(left, right) => (left, right, BinaryNode::Add),
+            BinaryNode::Subtract(left, right) => (left, right, BinaryNode::Subtract),
+            BinaryNode::Multiply(left, right) => (left, right, BinaryNode::Multiply),
+            BinaryNode::Frac(left, right) => (left, right, BinaryNode::Frac),
+            BinaryNode::Pow(left, right) => (left, right, BinaryNode::Pow),
+            BinaryNode::Log(left, right) => (left, right, BinaryNode::Log),
+        };
+    let left = initialize_child(*left, variables, bound);
+    let right = initialize_child(*right, variables, bound);
+    rebuild(Box::new(left), Box::new(right)).to_expr(true)
+}
+
+fn initialize_comparison(
+    comparison: ComparisonNode,
+    variables: &mut Vec<String>,
+    bound: &mut Vec<(String, usize)>,
+) -> Expr {
+    let (left, right, rebuild): (Box<Expr>, Box<Expr>, fn(Box<Expr>, Box<Expr>) -> ComparisonNode) =
+        match comparison {
+            ComparisonNode::Less(left, right) => (left, right, ComparisonNode::Less),
+            ComparisonNode::LessEq(left, right) => (left, right, ComparisonNode::LessEq),
+            ComparisonNode::Greater(left, right) => (left, right, ComparisonNode::Greater),
+            ComparisonNode::Equal(left, right) => (left, right, ComparisonNode::Equal),
+        };
+    let left = initialize_child(*left, variables, bound);
+    let right = initialize_child(*right, variables, bound);
+    rebuild(Box::new(left), Box::new(right)).to_expr(true)
+}
+
+fn initialize_ternary(
+    ternary: TernaryNode,
+    variables: &mut Vec<String>,
+    bound: &mut Vec<(String, usize)>,
+) -> Expr {
+    match ternary {
+        TernaryNode::Select(cond, if_true, if_false) => {
+            let cond = initialize_child(*cond, variables, bound);
+            let if_true = initialize_child(*if_true, variables, bound);
+            let if_false = initialize_child(*if_false, variables, bound);
+            TernaryNode::Select(Box::new(cond), Box::new(if_true), Box::new(if_false))
+                .to_expr(true)
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::structs::initialized_variable::InitializedVariable;
 use crate::structs::uninitialized_variable::UninitializedVariable;
 
 use super::{expr::Expr, initialized_leaf::InitializedLeaf, uninitialized_expr::UninitializedExpr};
@@ -13,9 +14,17 @@ pub enum UninitializedLeaf {
 
 impl UninitializedLeaf {
     /// # Initialize
-    /// Returns an initialized leaf node.
+    /// Returns an initialized leaf node. `index` is the already-resolved slot for this leaf: a
+    /// constant ignores it, and a variable is stamped with it directly - resolving *which* index a
+    /// given variable name should get is [`super::uninitialized_expr::UninitializedExpr::initialize`]'s
+    /// job, since that's the only place with enough context (the whole tree) to dedupe names.
     pub fn initialize(self, index: usize) -> InitializedLeaf {
-        unimplemented!()
+        match self {
+            UninitializedLeaf::Constant(value) => InitializedLeaf::Constant(value),
+            UninitializedLeaf::Variable(variable) => {
+                InitializedLeaf::Variable(InitializedVariable::new(variable, index))
+            }
+        }
     }
 
     /// # New variable
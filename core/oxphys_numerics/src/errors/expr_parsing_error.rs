@@ -1,8 +1,17 @@
 use std::fmt;
 
+use crate::structs::span::Span;
+
 #[derive(Debug)]
 pub struct ExprParsingError {
     details: String,
+
+    /// The source text the error occurred in, if the error originated from the text front-end
+    /// (see [`crate::parse`]). Kept alongside `span` so `Display` can render a caret underline.
+    source: Option<String>,
+
+    /// The byte-range in `source` that the error points at.
+    span: Option<Span>,
 }
 
 impl ExprParsingError {
@@ -15,13 +24,50 @@ impl ExprParsingError {
                 "The lengths of the variables in the expression do not match: {:?}",
                 lengths
             ),
+            source: None,
+            span: None,
+        }
+    }
+
+    /// # New syntax error
+    /// Create a new ExprParsingError from a descriptive message, with no source location
+    /// attached. Prefer [`ExprParsingError::new_syntax_at`] when a span is available.
+    pub(crate) fn new_syntax(message: String) -> ExprParsingError {
+        ExprParsingError {
+            details: message,
+            source: None,
+            span: None,
+        }
+    }
+
+    /// # New syntax error at
+    /// Create a new ExprParsingError from a descriptive message together with the source text
+    /// and the span of the offending token/subexpression, so [`Display`](fmt::Display) can
+    /// render a caret underline pointing at it.
+    pub(crate) fn new_syntax_at(message: String, source: &str, span: Span) -> ExprParsingError {
+        ExprParsingError {
+            details: message,
+            source: Some(source.to_string()),
+            span: Some(span),
         }
     }
 }
 
 impl fmt::Display for ExprParsingError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "ExprParsingError: {}", self.details)
+        match (&self.source, &self.span) {
+            (Some(source), Some(span)) => {
+                let underline_len = (span.end - span.start).max(1);
+                let padding = " ".repeat(span.start);
+                let carets = "^".repeat(underline_len);
+                write!(
+                    f,
+                    "ExprParsingError: {}\n{}\n{}{}",
+                    self.details, source, padding, carets
+                )
+            }
+            _ => write!(f, "ExprParsingError: {}", self.details),
+        }
     }
 }
 
@@ -1,5 +1,4 @@
 pub mod expr_parsing_error;
-pub mod expression_error;
 pub mod length_mismatch_error;
 pub mod no_variable_error;
 pub mod unsupported_type_error;
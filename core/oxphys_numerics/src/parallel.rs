@@ -0,0 +1,108 @@
+//! # Parallel module
+//!
+//! Work-splitting helpers for evaluating an `Expr` across many sample points at once.
+//! `evaluate_vec` JIT-compiles the expression once (see [`Expr::compile_nd_cse`]) and then fans a
+//! shared reference to the result out across a handful of scoped threads: `CompiledExpressionND`
+//! is `Sync`, so `&CompiledExpressionND` can be read concurrently by every worker with no locking.
+//! Gated behind the `jit` feature, since it fans work out across a JIT-compiled function.
+
+#![cfg(feature = "jit")]
+
+use std::thread;
+
+use crate::enums::expr::Expr;
+use crate::errors::length_mismatch_error::LengthMismatchError;
+use crate::traits::expression::CompiledExpressionND;
+
+/// Below this many sample points, `evaluate_vec` just runs serially on the calling thread: the
+/// overhead of spawning worker threads and splitting the range outweighs any parallel speedup.
+const PARALLEL_EVALUATE_VEC_THRESHOLD: usize = 4096;
+
+impl Expr {
+    /// # Evaluate vec
+    /// JIT-compile `self` once, then evaluate it at every sample point described by `variables`
+    /// (one `Vec<f64>` per variable). NumPy-style broadcasting applies: the common length is the
+    /// max of every variable's length, and any variable whose vector has length 1 is treated as a
+    /// constant repeated across that common length - e.g. `f(x, c)` can be evaluated over a
+    /// sampled `x` with a fixed scalar `c` without manually replicating `c` into a full vector.
+    /// Only a length that's neither the common length nor 1 is rejected as a
+    /// [`LengthMismatchError`]. Above `PARALLEL_EVALUATE_VEC_THRESHOLD` points, the index range is
+    /// split into a power-of-two number of contiguous chunks derived from the available CPU
+    /// count, and each chunk is evaluated on its own scoped thread, writing into a disjoint slice
+    /// of the output. Below the threshold, everything runs serially on the calling thread instead.
+    ///
+    /// `self.contains_let()` selects the compile path: `compile_nd_cse`'s constant folding and
+    /// common-subexpression elimination benefit every other expression, but it hash-conses
+    /// through a path that rejects `Let` (see [`crate::enums::initialized_expr::InitializedExpr`]),
+    /// so a let-bound expression is compiled via the plain tree-walking `compile_nd` instead, which
+    /// already supports it.
+    pub fn evaluate_vec(&self, variables: &[Vec<f64>]) -> Result<Vec<f64>, LengthMismatchError> {
+        let lengths: Vec<usize> = variables.iter().map(Vec::len).collect();
+        let num_points = lengths.iter().copied().max().unwrap_or(0);
+        if lengths.iter().any(|&len| len != num_points && len != 1) {
+            return Err(LengthMismatchError::new(lengths));
+        }
+
+        let f = if self.contains_let() {
+            self.compile_nd()
+        } else {
+            self.compile_nd_cse()
+        }
+        .expect("evaluate_vec: failed to JIT-compile expression");
+        let num_variables = variables.len();
+
+        let mut output = vec![0.0; num_points];
+        if num_points < PARALLEL_EVALUATE_VEC_THRESHOLD {
+            evaluate_range(&f, variables, &lengths, num_variables, 0, &mut output);
+            return Ok(output);
+        }
+
+        let chunk_size = num_points.div_ceil(worker_count(num_points));
+        thread::scope(|scope| {
+            for (chunk_index, chunk_out) in output.chunks_mut(chunk_size).enumerate() {
+                let start = chunk_index * chunk_size;
+                scope.spawn(move || {
+                    evaluate_range(&f, variables, &lengths, num_variables, start, chunk_out)
+                });
+            }
+        });
+
+        Ok(output)
+    }
+}
+
+/// # Worker count
+/// The number of chunks to split `num_points` samples across: the largest power of two not
+/// exceeding the number of logical CPUs (so e.g. a 6-core machine splits 4 ways, not 6), capped at
+/// `num_points` so a tiny input never produces empty chunks.
+fn worker_count(num_points: usize) -> usize {
+    let cpus = thread::available_parallelism()
+        .map(std::num::NonZeroUsize::get)
+        .unwrap_or(1);
+    let pow2 = 1usize << cpus.ilog2();
+    pow2.min(num_points.max(1))
+}
+
+/// # Evaluate range
+/// Evaluate `f` at the contiguous range of sample points starting at `start`, writing the results
+/// into `out`. Each point's row of variable values is gathered into a small scratch buffer before
+/// the call, since `CompiledExpressionND` expects them laid out contiguously. A variable is
+/// indexed with `point % lengths[var_index]`, so a length-1 variable always reads its single
+/// value while a full-length variable reads the point itself.
+fn evaluate_range(
+    f: &CompiledExpressionND,
+    variables: &[Vec<f64>],
+    lengths: &[usize],
+    num_variables: usize,
+    start: usize,
+    out: &mut [f64],
+) {
+    let mut row = vec![0.0; num_variables];
+    for (offset, value) in out.iter_mut().enumerate() {
+        let point = start + offset;
+        for (var_index, row_value) in row.iter_mut().enumerate() {
+            *row_value = variables[var_index][point % lengths[var_index]];
+        }
+        *value = f.call(row.as_ptr(), num_variables);
+    }
+}
@@ -0,0 +1,652 @@
+//! # Parse module
+//!
+//! This module provides a text front-end for `oxphys_numerics`: a small tokenizer and a
+//! Pratt/precedence-climbing parser that turn a source string like `"a*x + log(2, x) - 3"`
+//! directly into an [`UninitializedExpr`] tree, without the caller having to hand-assemble
+//! `BinaryNode`/`UnaryNode`/`LeafNode` values. Every token carries a [`Span`] into the original
+//! source so parse errors can point a caret at the exact offending text.
+
+use crate::enums::{
+    binary_node::BinaryNode, comparison_node::ComparisonNode, ternary_node::TernaryNode,
+    uninitialized_expr::UninitializedExpr, uninitialized_leaf::UninitializedLeaf,
+    unary_node::UnaryNode,
+};
+use crate::errors::expr_parsing_error::ExprParsingError;
+use crate::structs::span::Span;
+
+/// # Token
+/// A single lexical token in an `oxphys_numerics` expression string.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    Comma,
+    Equals,
+    EqualsEquals,
+    Less,
+    LessEq,
+    Greater,
+    LParen,
+    RParen,
+}
+
+/// # Spanned token
+/// A token together with the byte-range it came from in the source string.
+#[derive(Debug, Clone, PartialEq)]
+struct SpannedToken {
+    token: Token,
+    span: Span,
+}
+
+/// # Tokenize
+/// Turn a source string into a flat list of spanned tokens.
+fn tokenize(src: &str) -> Result<Vec<SpannedToken>, ExprParsingError> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = src.chars().collect();
+    let mut i = 0;
+
+    macro_rules! push_single {
+        ($token:expr) => {{
+            tokens.push(SpannedToken {
+                token: $token,
+                span: Span::new(i, i + 1),
+            });
+            i += 1;
+        }};
+    }
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '+' => push_single!(Token::Plus),
+            '-' => push_single!(Token::Minus),
+            '*' => push_single!(Token::Star),
+            '/' => push_single!(Token::Slash),
+            '^' => push_single!(Token::Caret),
+            ',' => push_single!(Token::Comma),
+            '(' => push_single!(Token::LParen),
+            ')' => push_single!(Token::RParen),
+            '=' => {
+                let start = i;
+                if chars.get(i + 1) == Some(&'=') {
+                    i += 2;
+                    tokens.push(SpannedToken {
+                        token: Token::EqualsEquals,
+                        span: Span::new(start, i),
+                    });
+                } else {
+                    i += 1;
+                    tokens.push(SpannedToken {
+                        token: Token::Equals,
+                        span: Span::new(start, i),
+                    });
+                }
+            }
+            '<' => {
+                let start = i;
+                if chars.get(i + 1) == Some(&'=') {
+                    i += 2;
+                    tokens.push(SpannedToken {
+                        token: Token::LessEq,
+                        span: Span::new(start, i),
+                    });
+                } else {
+                    i += 1;
+                    tokens.push(SpannedToken {
+                        token: Token::Less,
+                        span: Span::new(start, i),
+                    });
+                }
+            }
+            '>' => push_single!(Token::Greater),
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let span = Span::new(start, i);
+                let value = text.parse::<f64>().map_err(|_| {
+                    ExprParsingError::new_syntax_at(format!("invalid number '{}'", text), src, span)
+                })?;
+                tokens.push(SpannedToken {
+                    token: Token::Number(value),
+                    span,
+                });
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                tokens.push(SpannedToken {
+                    token: Token::Ident(text),
+                    span: Span::new(start, i),
+                });
+            }
+            other => {
+                return Err(ExprParsingError::new_syntax_at(
+                    format!("unexpected character '{}'", other),
+                    src,
+                    Span::new(i, i + 1),
+                ))
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// # Binding power
+/// Left and right binding powers for each infix operator, following the precedence-climbing
+/// approach: comparisons (`<`, `<=`, `>`, `==`) = 5, `+`/`-` = 10, `*`/`/` = 20, and `^` = 40,
+/// right-associative (its right binding power is lower than its left, so it recurses into itself
+/// on the right). Comparisons bind the loosest, matching how C-family languages read `a + 1 < b`
+/// as `(a + 1) < b`.
+fn infix_binding_power(token: &Token) -> Option<(u8, u8)> {
+    match token {
+        Token::Less | Token::LessEq | Token::Greater | Token::EqualsEquals => Some((5, 6)),
+        Token::Plus | Token::Minus => Some((10, 11)),
+        Token::Star | Token::Slash => Some((20, 21)),
+        Token::Caret => Some((41, 40)),
+        _ => None,
+    }
+}
+
+/// # Unary minus binding power
+/// The binding power of a prefix `-`, used when parsing e.g. `-x^2`.
+const UNARY_MINUS_BINDING_POWER: u8 = 30;
+
+/// # Parser
+/// Holds the token stream, the current read position, and the original source text (needed to
+/// render caret-underlined errors).
+struct Parser<'a> {
+    source: &'a str,
+    tokens: &'a [SpannedToken],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|t| &t.token)
+    }
+
+    /// # Current span
+    /// The span of the token at the current position, or a zero-width span at the end of the
+    /// source if we've run out of tokens (used for "unexpected end of input" errors).
+    fn current_span(&self) -> Span {
+        match self.tokens.get(self.position) {
+            Some(spanned) => spanned.span,
+            None => {
+                let end = self.source.len();
+                Span::new(end, end)
+            }
+        }
+    }
+
+    fn advance(&mut self) -> Option<SpannedToken> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn error_here(&self, message: String) -> ExprParsingError {
+        ExprParsingError::new_syntax_at(message, self.source, self.current_span())
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), ExprParsingError> {
+        let span = self.current_span();
+        match self.advance() {
+            Some(spanned) if &spanned.token == expected => Ok(()),
+            Some(spanned) => Err(ExprParsingError::new_syntax_at(
+                format!("expected {:?}, found {:?}", expected, spanned.token),
+                self.source,
+                spanned.span,
+            )),
+            None => Err(ExprParsingError::new_syntax_at(
+                format!("expected {:?}, found end of input", expected),
+                self.source,
+                span,
+            )),
+        }
+    }
+
+    /// # Expect keyword
+    /// Like `expect`, but for a bare identifier that's being used as a keyword (`let`, `in`)
+    /// rather than a variable name.
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), ExprParsingError> {
+        let span = self.current_span();
+        match self.advance() {
+            Some(SpannedToken {
+                token: Token::Ident(name),
+                ..
+            }) if name == keyword => Ok(()),
+            Some(spanned) => Err(ExprParsingError::new_syntax_at(
+                format!("expected '{}', found {:?}", keyword, spanned.token),
+                self.source,
+                spanned.span,
+            )),
+            None => Err(ExprParsingError::new_syntax_at(
+                format!("expected '{}', found end of input", keyword),
+                self.source,
+                span,
+            )),
+        }
+    }
+
+    /// # Parse expression
+    /// Parse an expression with binding power at least `min_bp`, recursing into operators of
+    /// higher precedence before returning to the caller.
+    fn parse_expression(&mut self, min_bp: u8) -> Result<UninitializedExpr, ExprParsingError> {
+        let mut left = self.parse_prefix()?;
+
+        loop {
+            let Some(operator) = self.peek().cloned() else {
+                break;
+            };
+
+            let Some((left_bp, right_bp)) = infix_binding_power(&operator) else {
+                break;
+            };
+
+            if left_bp < min_bp {
+                break;
+            }
+
+            self.advance();
+            let right = self.parse_expression(right_bp)?;
+            left = apply_binary_operator(&operator, left, right);
+        }
+
+        Ok(left)
+    }
+
+    /// # Parse prefix
+    /// Parse a single prefix expression: a unary minus, a let-binding, or a primary expression
+    /// (number, variable, function call, or parenthesized expression).
+    fn parse_prefix(&mut self) -> Result<UninitializedExpr, ExprParsingError> {
+        if self.peek() == Some(&Token::Minus) {
+            self.advance();
+            let operand = self.parse_expression(UNARY_MINUS_BINDING_POWER)?;
+            return Ok(-operand);
+        }
+
+        if let Some(Token::Ident(name)) = self.peek() {
+            if name == "let" {
+                return self.parse_let();
+            }
+        }
+
+        self.parse_primary()
+    }
+
+    /// # Parse let
+    /// Parse `let NAME = VALUE in BODY`. `NAME` shares its namespace with ordinary variables, so
+    /// `initialize` is the point where a reference to `NAME` inside `BODY` is told apart from a
+    /// free variable of the same name.
+    fn parse_let(&mut self) -> Result<UninitializedExpr, ExprParsingError> {
+        self.advance(); // consume `let`
+
+        let name = match self.advance() {
+            Some(SpannedToken {
+                token: Token::Ident(name),
+                ..
+            }) => name,
+            Some(spanned) => {
+                return Err(ExprParsingError::new_syntax_at(
+                    format!("expected a name after 'let', found {:?}", spanned.token),
+                    self.source,
+                    spanned.span,
+                ))
+            }
+            None => return Err(self.error_here("expected a name after 'let'".to_string())),
+        };
+
+        self.expect(&Token::Equals)?;
+        let value = self.parse_expression(0)?;
+        self.expect_keyword("in")?;
+        let body = self.parse_expression(0)?;
+
+        Ok(UninitializedExpr::Let {
+            name,
+            value: Box::new(value.to_expr()),
+            body: Box::new(body.to_expr()),
+        })
+    }
+
+    /// # Parse primary
+    /// Parse a number, a bare variable, a function call, or a parenthesized sub-expression.
+    fn parse_primary(&mut self) -> Result<UninitializedExpr, ExprParsingError> {
+        match self.advance() {
+            Some(SpannedToken {
+                token: Token::Number(value),
+                ..
+            }) => Ok(UninitializedExpr::Leaf(UninitializedLeaf::new_constant(
+                value,
+            ))),
+            Some(SpannedToken {
+                token: Token::Ident(name),
+                ..
+            }) => {
+                if self.peek() == Some(&Token::LParen) {
+                    self.parse_call(name)
+                } else {
+                    Ok(UninitializedExpr::Leaf(UninitializedLeaf::new_variable(name)))
+                }
+            }
+            Some(SpannedToken {
+                token: Token::LParen,
+                ..
+            }) => {
+                let inner = self.parse_expression(0)?;
+                self.expect(&Token::RParen)?;
+                Ok(inner)
+            }
+            Some(spanned) => Err(ExprParsingError::new_syntax_at(
+                format!("unexpected token {:?}", spanned.token),
+                self.source,
+                spanned.span,
+            )),
+            None => Err(self.error_here("unexpected end of input".to_string())),
+        }
+    }
+
+    /// # Parse call
+    /// Parse a function call `name(arg0, arg1, ...)`, mapping known names onto `UnaryNode`/
+    /// `BinaryNode` variants.
+    fn parse_call(&mut self, name: String) -> Result<UninitializedExpr, ExprParsingError> {
+        // `parse_primary` has already consumed the name token; point errors at it rather than at
+        // the `(` that follows.
+        let call_span = self.tokens[self.position - 1].span;
+        self.expect(&Token::LParen)?;
+
+        let mut args = vec![self.parse_expression(0)?];
+        while self.peek() == Some(&Token::Comma) {
+            self.advance();
+            args.push(self.parse_expression(0)?);
+        }
+        self.expect(&Token::RParen)?;
+
+        match (name.as_str(), args.len()) {
+            ("sqrt", 1) => Ok(unary_call(UnaryNode::Sqrt, args)),
+            ("sin", 1) => Ok(unary_call(UnaryNode::Sin, args)),
+            ("cos", 1) => Ok(unary_call(UnaryNode::Cos, args)),
+            ("exp", 1) => Ok(unary_call(UnaryNode::Exp, args)),
+            ("ln", 1) => Ok(unary_call(UnaryNode::Ln, args)),
+            ("log", 2) => {
+                let mut args = args.into_iter();
+                let base = args.next().unwrap();
+                let argument = args.next().unwrap();
+                Ok(UninitializedExpr::Binary(BinaryNode::Log(
+                    Box::new(base.to_expr()),
+                    Box::new(argument.to_expr()),
+                )))
+            }
+            ("select", 3) => {
+                let mut args = args.into_iter();
+                let cond = args.next().unwrap();
+                let if_true = args.next().unwrap();
+                let if_false = args.next().unwrap();
+                Ok(UninitializedExpr::Ternary(TernaryNode::Select(
+                    Box::new(cond.to_expr()),
+                    Box::new(if_true.to_expr()),
+                    Box::new(if_false.to_expr()),
+                )))
+            }
+            (name, arity) => Err(ExprParsingError::new_syntax_at(
+                format!("unknown function '{}' with {} argument(s)", name, arity),
+                self.source,
+                call_span,
+            )),
+        }
+    }
+}
+
+/// # Unary call
+/// Build an `UninitializedExpr::Unary` node from a single-argument function call.
+fn unary_call(
+    variant: fn(Box<crate::enums::expr::Expr>) -> UnaryNode,
+    args: Vec<UninitializedExpr>,
+) -> UninitializedExpr {
+    let mut args = args.into_iter();
+    let argument = args.next().unwrap();
+    UninitializedExpr::Unary(variant(Box::new(argument.to_expr())))
+}
+
+/// # Apply binary operator
+/// Combine `left` and `right` using the operator token, relying on `UninitializedExpr`'s
+/// operator overloads where they exist and building `Pow` directly where they don't.
+fn apply_binary_operator(
+    operator: &Token,
+    left: UninitializedExpr,
+    right: UninitializedExpr,
+) -> UninitializedExpr {
+    match operator {
+        Token::Plus => left + right,
+        Token::Minus => left - right,
+        Token::Star => left * right,
+        Token::Slash => left / right,
+        Token::Caret => UninitializedExpr::Binary(BinaryNode::Pow(
+            Box::new(left.to_expr()),
+            Box::new(right.to_expr()),
+        )),
+        Token::Less => UninitializedExpr::Comparison(ComparisonNode::Less(
+            Box::new(left.to_expr()),
+            Box::new(right.to_expr()),
+        )),
+        Token::LessEq => UninitializedExpr::Comparison(ComparisonNode::LessEq(
+            Box::new(left.to_expr()),
+            Box::new(right.to_expr()),
+        )),
+        Token::Greater => UninitializedExpr::Comparison(ComparisonNode::Greater(
+            Box::new(left.to_expr()),
+            Box::new(right.to_expr()),
+        )),
+        Token::EqualsEquals => UninitializedExpr::Comparison(ComparisonNode::Equal(
+            Box::new(left.to_expr()),
+            Box::new(right.to_expr()),
+        )),
+        other => unreachable!("{:?} is not an infix operator", other),
+    }
+}
+
+/// # Parse
+/// Parse a source string into an [`UninitializedExpr`] tree. Identifiers are collected as
+/// [`UninitializedLeaf::new_variable`] leaves in first-seen order; the caller hands the result to
+/// the existing initialization step to assign each distinct variable an index. On failure, the
+/// returned [`ExprParsingError`] carries the source text and the offending span, so printing it
+/// renders a caret underline pointing at the exact problem.
+///
+/// ```text
+/// parse("a*x + log(2, x) - 3")
+/// ```
+/// parses as `(a * x) + log(2, x) - 3`, with binding powers `+`/`-` = 10, `*`/`/` = 20, unary
+/// minus = 30, and `^` = 40 (right-associative).
+pub fn parse(src: &str) -> Result<UninitializedExpr, ExprParsingError> {
+    let tokens = tokenize(src)?;
+    let mut parser = Parser {
+        source: src,
+        tokens: &tokens,
+        position: 0,
+    };
+
+    let expr = parser.parse_expression(0)?;
+
+    if parser.position != tokens.len() {
+        let trailing = &tokens[parser.position];
+        return Err(ExprParsingError::new_syntax_at(
+            format!("unexpected trailing token {:?}", trailing.token),
+            src,
+            trailing.span,
+        ));
+    }
+
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        let expr = parse("3.5").unwrap();
+        assert!(matches!(
+            expr,
+            UninitializedExpr::Leaf(UninitializedLeaf::Constant(value)) if value == 3.5
+        ));
+    }
+
+    #[test]
+    fn test_parse_variable() {
+        let expr = parse("x").unwrap();
+        assert!(matches!(expr, UninitializedExpr::Leaf(UninitializedLeaf::Variable(_))));
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        // "a + x * y" should parse as "a + (x * y)", i.e. the outermost node is an Add.
+        let expr = parse("a + x * y").unwrap();
+        assert!(matches!(expr, UninitializedExpr::Binary(BinaryNode::Add(_, _))));
+    }
+
+    #[test]
+    fn test_parse_right_associative_power() {
+        // "x^y^z" should parse as "x^(y^z)".
+        let expr = parse("x^y^z").unwrap();
+        match expr {
+            UninitializedExpr::Binary(BinaryNode::Pow(_, right)) => {
+                assert!(matches!(*right, crate::enums::expr::Expr::Uninitialized(
+                    UninitializedExpr::Binary(BinaryNode::Pow(_, _))
+                )));
+            }
+            _ => panic!("expected a Pow node"),
+        }
+    }
+
+    #[test]
+    fn test_parse_function_call() {
+        let expr = parse("sin(x)").unwrap();
+        assert!(matches!(expr, UninitializedExpr::Unary(UnaryNode::Sin(_))));
+    }
+
+    #[test]
+    fn test_parse_log_call() {
+        let expr = parse("log(2, x)").unwrap();
+        assert!(matches!(expr, UninitializedExpr::Binary(BinaryNode::Log(_, _))));
+    }
+
+    #[test]
+    fn test_parse_comparison() {
+        let expr = parse("x < 3").unwrap();
+        assert!(matches!(
+            expr,
+            UninitializedExpr::Comparison(ComparisonNode::Less(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_parse_comparison_binds_looser_than_arithmetic() {
+        // "x + 1 < y" should parse as "(x + 1) < y", i.e. the outermost node is the comparison.
+        let expr = parse("x + 1 < y").unwrap();
+        match expr {
+            UninitializedExpr::Comparison(ComparisonNode::Less(left, _)) => {
+                assert!(matches!(
+                    *left,
+                    crate::enums::expr::Expr::Uninitialized(UninitializedExpr::Binary(
+                        BinaryNode::Add(_, _)
+                    ))
+                ));
+            }
+            _ => panic!("expected a Less comparison"),
+        }
+    }
+
+    #[test]
+    fn test_parse_select_call() {
+        let expr = parse("select(x < 0, -x, x)").unwrap();
+        assert!(matches!(
+            expr,
+            UninitializedExpr::Ternary(TernaryNode::Select(_, _, _))
+        ));
+    }
+
+    #[test]
+    fn test_parse_unary_minus() {
+        let expr = parse("-x").unwrap();
+        assert!(matches!(expr, UninitializedExpr::Unary(UnaryNode::Negate(_))));
+    }
+
+    #[test]
+    fn test_parse_parentheses() {
+        let expr = parse("(a + x) * y").unwrap();
+        assert!(matches!(expr, UninitializedExpr::Binary(BinaryNode::Multiply(_, _))));
+    }
+
+    #[test]
+    fn test_parse_unknown_function_errors() {
+        assert!(parse("frobnicate(x)").is_err());
+    }
+
+    #[test]
+    fn test_parse_unbalanced_parentheses_errors() {
+        assert!(parse("(a + x").is_err());
+    }
+
+    #[test]
+    fn test_error_renders_caret_at_unknown_function() {
+        let err = parse("1 + frobnicate(x)").unwrap_err();
+        let rendered = err.to_string();
+        assert!(rendered.contains("1 + frobnicate(x)"));
+        // The caret line should line up under "frobnicate", i.e. 4 spaces then carets.
+        assert!(rendered.contains("\n    ^"));
+    }
+
+    #[test]
+    fn test_error_renders_caret_at_unexpected_character() {
+        let err = parse("1 + @").unwrap_err();
+        let rendered = err.to_string();
+        assert!(rendered.ends_with("^"));
+    }
+
+    #[test]
+    fn test_parse_let_binding() {
+        let expr = parse("let y = x * x in y + y").unwrap();
+        match expr {
+            UninitializedExpr::Let { name, .. } => assert_eq!(name, "y"),
+            _ => panic!("expected a Let node"),
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_let_binding() {
+        let expr = parse("let a = 1 in let b = 2 in a + b").unwrap();
+        match expr {
+            UninitializedExpr::Let { name, body, .. } => {
+                assert_eq!(name, "a");
+                assert!(matches!(
+                    *body,
+                    crate::enums::expr::Expr::Uninitialized(UninitializedExpr::Let { .. })
+                ));
+            }
+            _ => panic!("expected a Let node"),
+        }
+    }
+
+    #[test]
+    fn test_parse_let_missing_in_errors() {
+        assert!(parse("let x = 1 x").is_err());
+    }
+}
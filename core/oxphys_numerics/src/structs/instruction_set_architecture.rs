@@ -1,3 +1,5 @@
+#![cfg(feature = "jit")]
+
 use std::sync::Arc;
 
 use cranelift_codegen::ir::types;
@@ -5,6 +7,8 @@ use cranelift_codegen::isa::TargetIsa;
 use cranelift_codegen::settings::Configurable;
 use cranelift_native;
 
+use crate::enums::opt_level::OptLevel;
+
 /// # Instruction set architecture
 /// This struct contains information relating to the instruction set architecture that we're
 /// compiling for.
@@ -14,16 +18,24 @@ pub(crate) struct InstructionSetArchitecture {
 
 impl InstructionSetArchitecture {
     /// # New
-    /// Create a new ISA corresponding to the current platform.
+    /// Create a new ISA corresponding to the current platform, optimizing aggressively for
+    /// runtime speed. Equivalent to `Self::with_opt_level(OptLevel::Speed)`.
     pub fn current_platform() -> Self {
+        Self::with_opt_level(OptLevel::Speed)
+    }
+
+    /// # With opt level
+    /// Create a new ISA corresponding to the current platform, compiled with the given
+    /// [`OptLevel`].
+    pub fn with_opt_level(opt_level: OptLevel) -> Self {
         // Use cranelift_native to configure ISA for your current platform (e.g. Apple Silicon).
         let isa_builder = cranelift_native::builder().expect("Failed to create ISA builder");
 
-        // Create a default flags builder and manually pass in the opt_level "speed" flag. We're
-        // generally very performance sensitive here, so we'll always want the most aggressive
-        // optimization level here.
+        // Create a default flags builder and pass in the requested opt_level flag.
         let mut flag_builder = cranelift_codegen::settings::builder();
-        flag_builder.set("opt_level", "speed").unwrap();
+        flag_builder
+            .set("opt_level", opt_level.cranelift_setting())
+            .unwrap();
         let flags = cranelift_codegen::settings::Flags::new(flag_builder);
         let isa = isa_builder.finish(flags).expect("Failed to create ISA");
 
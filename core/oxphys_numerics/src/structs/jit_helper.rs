@@ -1,3 +1,5 @@
+#![cfg(feature = "jit")]
+
 use cranelift_codegen::ir::types;
 use cranelift_codegen::ir::{AbiParam, Signature};
 use cranelift_codegen::isa::CallConv;
@@ -7,6 +9,7 @@ use cranelift_jit::{JITBuilder, JITModule};
 use cranelift_module::{FuncId, Linkage, Module};
 
 use super::instruction_set_architecture::InstructionSetArchitecture;
+use super::libm_imports::{LibmFuncIds, LibmImports};
 
 /// # Jit helper
 /// This struct contains some common cranelift objects that are needed to run the oxphys_numerics
@@ -23,22 +26,53 @@ pub(crate) struct JITHelper {
 
     /// The compilation context.
     context: Context,
+
+    /// The module-level FuncIds for the libm transcendentals, declared once so every node that
+    /// needs one can import it into the function currently being built.
+    libm_func_ids: LibmFuncIds,
 }
 
 impl JITHelper {
     /// # New
-    /// Create a new JITHelper.
+    /// Create a new JITHelper whose function returns a single value of `return_type`.
     pub(crate) fn new(
         isa: InstructionSetArchitecture,
         parameters: Vec<types::Type>,
         return_type: types::Type,
+    ) -> Self {
+        Self::with_signature(isa, parameters, vec![return_type])
+    }
+
+    /// # New void
+    /// Create a new JITHelper whose function returns nothing, because it writes its result
+    /// through an output-pointer parameter instead (see `Expression::compile_nd_vec`).
+    pub(crate) fn new_void(isa: InstructionSetArchitecture, parameters: Vec<types::Type>) -> Self {
+        Self::with_signature(isa, parameters, vec![])
+    }
+
+    /// # With signature
+    /// Shared setup for `new`/`new_void`: builds the module, registers the libm symbols, and
+    /// declares a function with the given parameter and return types.
+    fn with_signature(
+        isa: InstructionSetArchitecture,
+        parameters: Vec<types::Type>,
+        returns: Vec<types::Type>,
     ) -> Self {
         // Create a JIT builder with the appropriate ISA.
-        let jit_builder = JITBuilder::with_isa(
+        let mut jit_builder = JITBuilder::with_isa(
             isa.cranelift_isa(),
             cranelift_module::default_libcall_names(),
         );
 
+        // Register the host libm symbols that the transcendental UnaryNode variants call out to:
+        // there's no hardware instruction for sin/cos/exp/ln, so the generated code needs to be
+        // able to resolve a call to the real implementation at JIT time.
+        jit_builder.symbol("sin", (f64::sin as fn(f64) -> f64) as *const u8);
+        jit_builder.symbol("cos", (f64::cos as fn(f64) -> f64) as *const u8);
+        jit_builder.symbol("exp", (f64::exp as fn(f64) -> f64) as *const u8);
+        jit_builder.symbol("log", (f64::ln as fn(f64) -> f64) as *const u8);
+        jit_builder.symbol("pow", (f64::powf as fn(f64, f64) -> f64) as *const u8);
+
         // Create a new module.
         let mut module = JITModule::new(jit_builder);
 
@@ -46,11 +80,13 @@ impl JITHelper {
         let mut function_signature =
             Signature::new(CallConv::triple_default(module.isa().triple()));
 
-        // Add the parameters and return value to the function signature.
+        // Add the parameters and return values to the function signature.
         for parameter in parameters {
             function_signature.params.push(AbiParam::new(parameter));
         }
-        function_signature.returns.push(AbiParam::new(return_type));
+        for return_type in returns {
+            function_signature.returns.push(AbiParam::new(return_type));
+        }
 
         // Declare the function.
         let function_id = module
@@ -62,12 +98,16 @@ impl JITHelper {
         context.func.signature = function_signature.clone();
         let function_context = FunctionBuilderContext::new();
 
+        // Declare the libm transcendentals once, against the module.
+        let libm_func_ids = LibmFuncIds::declare(&mut module);
+
         // Create a new JITHelper.
         JITHelper {
             module,
             function_id,
             function_context,
             context,
+            libm_func_ids,
         }
     }
 
@@ -77,17 +117,37 @@ impl JITHelper {
         FunctionBuilder::new(&mut self.context.func, &mut self.function_context)
     }
 
+    /// # Libm imports
+    /// Import the libm transcendentals into the function currently being built, returning their
+    /// `FuncRef`s. Must be called before `function_builder`, since both need a mutable borrow of
+    /// `context.func`.
+    pub(crate) fn libm_imports(&mut self) -> LibmImports {
+        self.libm_func_ids
+            .import_into(&mut self.module, &mut self.context.func)
+    }
+
     /// # Finalize
-    /// Define the finalized function, and finalize the module.
-    pub(crate) fn finalize(&mut self) -> *const u8 {
+    /// Define the finalized function, finalize the module, and hand back the module alongside a
+    /// callable function pointer into it. Consumes `self` (rather than taking `&mut self`)
+    /// because the returned pointer is only valid for as long as the `JITModule` that owns its
+    /// executable memory is kept alive - callers must hold on to `module` for exactly that long,
+    /// typically by wrapping it with the pointer in a
+    /// [`crate::traits::expression::CompiledFunction`].
+    pub(crate) fn finalize(self) -> (JITModule, *const u8) {
+        let JITHelper {
+            mut module,
+            function_id,
+            mut context,
+            ..
+        } = self;
+
         // Define and finalize the function.
-        self.module
-            .define_function(self.function_id, &mut self.context)
-            .unwrap();
-        self.module.clear_context(&mut self.context);
-        self.module.finalize_definitions().unwrap();
+        module.define_function(function_id, &mut context).unwrap();
+        module.clear_context(&mut context);
+        module.finalize_definitions().unwrap();
 
-        // Return a callable function pointer.
-        self.module.get_finalized_function(self.function_id)
+        // Return the module together with a callable function pointer into it.
+        let code = module.get_finalized_function(function_id);
+        (module, code)
     }
 }
@@ -0,0 +1,80 @@
+#![cfg(feature = "jit")]
+
+use cranelift_codegen::ir::{types, AbiParam, FuncRef, Function, Signature};
+use cranelift_codegen::isa::CallConv;
+use cranelift_module::{FuncId, Linkage, Module};
+
+/// # Libm func ids
+/// The module-level `FuncId`s for the libm transcendentals `UnaryNode` needs: there's no hardware
+/// instruction for `sin`/`cos`/`exp`/`ln`, so Cranelift has to call out to the host's math library
+/// instead, the same way a compiler targeting a CPU without a sqrt instruction emits a libcall for
+/// it. Declared once per module and shared by every function built in it.
+pub(crate) struct LibmFuncIds {
+    pub(crate) sin: FuncId,
+    pub(crate) cos: FuncId,
+    pub(crate) exp: FuncId,
+    pub(crate) ln: FuncId,
+
+    /// `pow(base, exponent) -> f64`, needed by `BinaryNode::Pow` since there's no hardware
+    /// instruction for general exponentiation either.
+    pub(crate) pow: FuncId,
+}
+
+impl LibmFuncIds {
+    /// # Declare
+    /// Declare `sin`/`cos`/`exp`/`log`/`pow` as imported functions in `module`. Callers using the
+    /// JIT backend must also register a symbol resolving each name to a real function pointer
+    /// (see `JITHelper::new`); callers emitting an AOT object can instead leave these as
+    /// unresolved relocations for the system linker to satisfy against libm (see
+    /// `crate::aot::link_shared_library`).
+    pub(crate) fn declare<M: Module>(module: &mut M) -> Self {
+        fn declare_with_arity<M: Module>(module: &mut M, name: &str, arity: usize) -> FuncId {
+            let mut signature = Signature::new(CallConv::triple_default(module.isa().triple()));
+            for _ in 0..arity {
+                signature.params.push(AbiParam::new(types::F64));
+            }
+            signature.returns.push(AbiParam::new(types::F64));
+            module
+                .declare_function(name, Linkage::Import, &signature)
+                .unwrap()
+        }
+
+        LibmFuncIds {
+            sin: declare_with_arity(module, "sin", 1),
+            cos: declare_with_arity(module, "cos", 1),
+            exp: declare_with_arity(module, "exp", 1),
+            ln: declare_with_arity(module, "log", 1),
+            pow: declare_with_arity(module, "pow", 2),
+        }
+    }
+
+    /// # Import into
+    /// Import each declared function into `func`, the function currently being built, returning
+    /// the resulting `FuncRef`s so `UnaryNode`/`BinaryNode::build_jit_nd` can
+    /// `builder.ins().call(..)` them.
+    pub(crate) fn import_into<M: Module>(
+        &self,
+        module: &mut M,
+        func: &mut Function,
+    ) -> LibmImports {
+        LibmImports {
+            sin: module.declare_func_in_func(self.sin, func),
+            cos: module.declare_func_in_func(self.cos, func),
+            exp: module.declare_func_in_func(self.exp, func),
+            ln: module.declare_func_in_func(self.ln, func),
+            pow: module.declare_func_in_func(self.pow, func),
+        }
+    }
+}
+
+/// # Libm imports
+/// Per-function `FuncRef`s for the libm transcendentals, scoped to whichever function is
+/// currently being built.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LibmImports {
+    pub(crate) sin: FuncRef,
+    pub(crate) cos: FuncRef,
+    pub(crate) exp: FuncRef,
+    pub(crate) ln: FuncRef,
+    pub(crate) pow: FuncRef,
+}
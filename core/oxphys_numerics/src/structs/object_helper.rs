@@ -0,0 +1,115 @@
+#![cfg(feature = "jit")]
+
+use cranelift_codegen::ir::types;
+use cranelift_codegen::ir::{AbiParam, Signature};
+use cranelift_codegen::isa::CallConv;
+use cranelift_codegen::Context;
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_module::{FuncId, Linkage, Module};
+use cranelift_object::{ObjectBuilder, ObjectModule};
+
+use super::instruction_set_architecture::InstructionSetArchitecture;
+use super::libm_imports::{LibmFuncIds, LibmImports};
+
+/// # Object helper
+/// The ahead-of-time counterpart to [`super::jit_helper::JITHelper`]: instead of finalizing the
+/// compiled function into memory for immediate use, it emits a relocatable object file containing
+/// the function under a caller-chosen exported symbol name.
+pub(crate) struct ObjectHelper {
+    /// The module that we're going to be adding the function to.
+    module: ObjectModule,
+
+    /// The function ID.
+    function_id: FuncId,
+
+    /// The function context.
+    function_context: FunctionBuilderContext,
+
+    /// The compilation context.
+    context: Context,
+
+    /// The module-level FuncIds for the libm transcendentals. Unlike `JITHelper`, no symbol
+    /// resolution happens here: these stay as unresolved imports in the emitted object, for the
+    /// system linker to satisfy against libm when the caller links the object into a shared
+    /// library (see `crate::aot::link_shared_library`).
+    libm_func_ids: LibmFuncIds,
+}
+
+impl ObjectHelper {
+    /// # New
+    /// Create a new ObjectHelper that will emit a function exported under `symbol_name`.
+    pub(crate) fn new(
+        isa: InstructionSetArchitecture,
+        symbol_name: &str,
+        parameters: Vec<types::Type>,
+        return_type: types::Type,
+    ) -> Self {
+        // Create an object builder with the appropriate ISA.
+        let object_builder = ObjectBuilder::new(
+            isa.cranelift_isa(),
+            symbol_name.to_string(),
+            cranelift_module::default_libcall_names(),
+        )
+        .unwrap();
+
+        // Create a new module.
+        let mut module = ObjectModule::new(object_builder);
+
+        // Create the function signature object, which will be used to declare the function.
+        let mut function_signature =
+            Signature::new(CallConv::triple_default(module.isa().triple()));
+
+        // Add the parameters and return value to the function signature.
+        for parameter in parameters {
+            function_signature.params.push(AbiParam::new(parameter));
+        }
+        function_signature.returns.push(AbiParam::new(return_type));
+
+        // Declare the function under the exported symbol name, so it's callable from outside the
+        // object file once linked.
+        let function_id = module
+            .declare_function(symbol_name, Linkage::Export, &function_signature)
+            .unwrap();
+
+        // Prepare the function context.
+        let mut context = module.make_context();
+        context.func.signature = function_signature.clone();
+        let function_context = FunctionBuilderContext::new();
+
+        let libm_func_ids = LibmFuncIds::declare(&mut module);
+
+        ObjectHelper {
+            module,
+            function_id,
+            function_context,
+            context,
+            libm_func_ids,
+        }
+    }
+
+    /// # Get function builder
+    /// Get a FunctionBuilder object that can be used to build the function's IR.
+    pub(crate) fn function_builder(&mut self) -> FunctionBuilder {
+        FunctionBuilder::new(&mut self.context.func, &mut self.function_context)
+    }
+
+    /// # Libm imports
+    /// Import the libm transcendentals into the function currently being built, returning their
+    /// `FuncRef`s. Must be called before `function_builder`, since both need a mutable borrow of
+    /// `context.func`.
+    pub(crate) fn libm_imports(&mut self) -> LibmImports {
+        self.libm_func_ids
+            .import_into(&mut self.module, &mut self.context.func)
+    }
+
+    /// # Finalize
+    /// Define the function, then emit the finished module as relocatable object bytes.
+    pub(crate) fn finalize(mut self) -> Vec<u8> {
+        self.module
+            .define_function(self.function_id, &mut self.context)
+            .unwrap();
+        self.module.clear_context(&mut self.context);
+
+        self.module.finish().emit().unwrap()
+    }
+}
@@ -0,0 +1,30 @@
+/// # Span
+/// A byte-range into a source string, following the "span all the things" approach: every token
+/// produced by the parser carries one of these so that parse errors can be rendered with a caret
+/// pointing at the offending source text. The parsed `UninitializedExpr` nodes carry no span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// The byte offset of the first byte covered by this span.
+    pub start: usize,
+
+    /// The byte offset one past the last byte covered by this span.
+    pub end: usize,
+}
+
+impl Span {
+    /// # New
+    /// Create a new span covering `[start, end)`.
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// # Merge
+    /// Create the smallest span that covers both `self` and `other`. The parser does not call
+    /// this; it only records the spans of single tokens.
+    pub fn merge(&self, other: &Span) -> Span {
+        Span {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
@@ -0,0 +1,189 @@
+#![cfg(feature = "jit")]
+
+//! # Transcendental kernels
+//!
+//! In-IR, branch-free implementations of `sin`/`cos`/`exp`/`ln`, built from `fmul`/`fadd`/
+//! `select` and a handful of integer bit-twiddling instructions rather than a libm call. Unlike
+//! `crate::structs::libm_imports::LibmImports`, nothing here crosses a function-call boundary, so
+//! the result inlines and constant-folds exactly like every other node `build_jit_nd` emits.
+//! Used by `UnaryNode`'s scalar `build_jit_1d`/`2d`/`3d`/`nd` paths; the vectorized
+//! `build_jit_nd_vec` path and `BinaryNode::Pow`'s general (non-integer-exponent) case still fall
+//! back to `libm`, since neither of those has an equally narrow range to reduce against yet.
+
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::{types, InstBuilder, MemFlags, Value};
+use cranelift_frontend::FunctionBuilder;
+
+/// # Horner
+/// Evaluate `coefficients[0] + coefficients[1]*x + coefficients[2]*x^2 + ...` via Horner's method,
+/// highest-degree term first. Shared by every polynomial kernel below.
+fn horner(builder: &mut FunctionBuilder, x: Value, coefficients: &[f64]) -> Value {
+    let (&last, rest) = coefficients.split_last().expect("horner needs at least one coefficient");
+    let mut accumulator = builder.ins().f64const(last);
+
+    for &coefficient in rest.iter().rev() {
+        let term = builder.ins().f64const(coefficient);
+        let scaled = builder.ins().fmul(accumulator, x);
+        accumulator = builder.ins().fadd(scaled, term);
+    }
+
+    accumulator
+}
+
+/// # Build sin cos
+/// Compute `(sin(x), cos(x))` together, since both share the same Payne-Hanek-style range
+/// reduction: let `t = x/pi`, `xi = round(2t)` (ties to even), and `xk = t - xi/2`, so
+/// `|xk| <= 1/4` and `u = pi*xk` lies in `[-pi/4, pi/4]`. Two short Taylor polynomials - odd for
+/// `sin(u)`, even for `cos(u)` - are then accurate on that narrow interval, and the low two bits
+/// of `xi` pick which polynomial is `sin`/`cos` and what sign each should have, exactly
+/// reconstructing the full-range answer from the quadrant `xi` landed in.
+pub(crate) fn build_sin_cos(builder: &mut FunctionBuilder, x: Value) -> (Value, Value) {
+    let pi = builder.ins().f64const(std::f64::consts::PI);
+    let t = builder.ins().fdiv(x, pi);
+
+    let two = builder.ins().f64const(2.0);
+    let two_t = builder.ins().fmul(t, two);
+    let xi_f = builder.ins().nearest(two_t);
+    let xi = builder.ins().fcvt_to_sint(types::I64, xi_f);
+
+    let half = builder.ins().f64const(0.5);
+    let xi_over_two = builder.ins().fmul(xi_f, half);
+    let xk = builder.ins().fsub(t, xi_over_two);
+    let u = builder.ins().fmul(xk, pi);
+    let u2 = builder.ins().fmul(u, u);
+
+    // sk ~= sin(u), odd series; ck ~= cos(u), even series - both only need to be accurate for
+    // |u| <= pi/4, so a handful of Taylor terms already gets well within f64 rounding error.
+    // Terms run through u^15 (sin) and u^16 (cos).
+    let odd_terms = horner(builder, u2, &[
+        1.0, -1.0 / 6.0, 1.0 / 120.0, -1.0 / 5040.0, 1.0 / 362880.0, -1.0 / 39916800.0,
+        1.0 / 6227020800.0, -1.0 / 1307674368000.0,
+    ]);
+    let sk = builder.ins().fmul(odd_terms, u);
+    let ck = horner(builder, u2, &[
+        1.0, -1.0 / 2.0, 1.0 / 24.0, -1.0 / 720.0, 1.0 / 40320.0, -1.0 / 3628800.0,
+        1.0 / 479001600.0, -1.0 / 87178291200.0, 1.0 / 20922789888000.0,
+    ]);
+
+    let zero = builder.ins().iconst(types::I64, 0);
+    let one = builder.ins().iconst(types::I64, 1);
+    let two_i = builder.ins().iconst(types::I64, 2);
+
+    // Bit 0 of xi: even quadrants keep sk/ck as sin/cos, odd quadrants swap them.
+    let bit0 = builder.ins().band(xi, one);
+    let bit0_clear = builder.ins().icmp(IntCC::Equal, bit0, zero);
+    let st = builder.ins().select(bit0_clear, sk, ck);
+    let ct = builder.ins().select(bit0_clear, ck, sk);
+
+    // Bit 1 of xi controls sin's sign.
+    let sin_sign_bit = builder.ins().band(xi, two_i);
+    let sin_positive = builder.ins().icmp(IntCC::Equal, sin_sign_bit, zero);
+    let negated_st = builder.ins().fneg(st);
+    let sin_value = builder.ins().select(sin_positive, st, negated_st);
+
+    // Bit 1 of (xi + 1) controls cos's sign - cos leads sin by one quadrant.
+    let xi_plus_one = builder.ins().iadd_imm(xi, 1);
+    let cos_sign_bit = builder.ins().band(xi_plus_one, two_i);
+    let cos_positive = builder.ins().icmp(IntCC::Equal, cos_sign_bit, zero);
+    let negated_ct = builder.ins().fneg(ct);
+    let cos_value = builder.ins().select(cos_positive, ct, negated_ct);
+
+    (sin_value, cos_value)
+}
+
+/// # Build sin
+/// Compute `sin(x)`. See [`build_sin_cos`] for the range reduction; prefer that directly when
+/// both `sin` and `cos` of the same argument are needed, since it shares the reduction between
+/// them instead of doing it twice.
+pub(crate) fn build_sin(builder: &mut FunctionBuilder, x: Value) -> Value {
+    build_sin_cos(builder, x).0
+}
+
+/// # Build cos
+/// Compute `cos(x)`. See [`build_sin_cos`].
+pub(crate) fn build_cos(builder: &mut FunctionBuilder, x: Value) -> Value {
+    build_sin_cos(builder, x).1
+}
+
+/// # Build exp
+/// Compute `exp(x)` via the standard `exp(x) = 2^k * exp(r)` reduction: `k = round(x/ln2)` and
+/// `r = x - k*ln2`, so `|r| <= ln2/2` and a short Taylor polynomial is accurate for `exp(r)`.
+/// `2^k` is then built directly as an `f64` bit pattern (`k` shifted into the exponent field)
+/// rather than by repeated squaring, so the scale-back is a single `fmul`.
+pub(crate) fn build_exp(builder: &mut FunctionBuilder, x: Value) -> Value {
+    let inv_ln2 = builder.ins().f64const(std::f64::consts::LOG2_E); // 1 / ln(2)
+    let k_f = builder.ins().fmul(x, inv_ln2);
+    let k_rounded = builder.ins().nearest(k_f);
+    let k = builder.ins().fcvt_to_sint(types::I64, k_rounded);
+
+    let ln2 = builder.ins().f64const(std::f64::consts::LN_2);
+    let k_ln2 = builder.ins().fmul(k_rounded, ln2);
+    let r = builder.ins().fsub(x, k_ln2);
+
+    let exp_r = horner(
+        builder,
+        r,
+        &[
+            1.0,
+            1.0,
+            1.0 / 2.0,
+            1.0 / 6.0,
+            1.0 / 24.0,
+            1.0 / 120.0,
+            1.0 / 720.0,
+        ],
+    );
+
+    let bias = builder.ins().iconst(types::I64, 1023);
+    let biased_exponent = builder.ins().iadd(k, bias);
+    let exponent_bits = builder.ins().ishl_imm(biased_exponent, 52);
+    let two_pow_k = builder.ins().bitcast(types::F64, MemFlags::new(), exponent_bits);
+
+    builder.ins().fmul(exp_r, two_pow_k)
+}
+
+/// # Build ln
+/// Compute `ln(x)` via the classic `frexp`-style decomposition `x = m * 2^e` with `m` re-centred
+/// to `[sqrt(2)/2, sqrt(2)]`, read straight out of `x`'s `f64` bit pattern (exponent field -> `e`,
+/// mantissa field with the exponent reset to `0` -> `m`), then `ln(x) = e*ln2 + ln(m)` with `ln(m)`
+/// computed from the odd `atanh`-style series `ln(m) = 2*atanh((m-1)/(m+1))`, which converges
+/// quickly once `m` is that close to `1`.
+pub(crate) fn build_ln(builder: &mut FunctionBuilder, x: Value) -> Value {
+    let bits = builder.ins().bitcast(types::I64, MemFlags::new(), x);
+
+    let exponent_mask = builder.ins().iconst(types::I64, 0x7FF);
+    let raw_exponent = builder.ins().ushr_imm(bits, 52);
+    let biased_exponent = builder.ins().band(raw_exponent, exponent_mask);
+    let bias = builder.ins().iconst(types::I64, 1023);
+    let exponent = builder.ins().isub(biased_exponent, bias);
+
+    let mantissa_mask = builder.ins().iconst(types::I64, 0x000F_FFFF_FFFF_FFFF);
+    let mantissa_bits = builder.ins().band(bits, mantissa_mask);
+    let unit_exponent_bits = builder.ins().iconst(types::I64, 1023i64 << 52);
+    let normalized_bits = builder.ins().bor(mantissa_bits, unit_exponent_bits);
+    let mantissa = builder.ins().bitcast(types::F64, MemFlags::new(), normalized_bits); // in [1, 2)
+
+    // Re-centre onto [sqrt(2)/2, sqrt(2)] so `ln(m)`'s series converges as fast as possible.
+    let sqrt2 = builder.ins().f64const(std::f64::consts::SQRT_2);
+    let mantissa_too_big = builder.ins().fcmp(FloatCC::GreaterThan, mantissa, sqrt2);
+    let half = builder.ins().f64const(0.5);
+    let halved_mantissa = builder.ins().fmul(mantissa, half);
+    let reduced_mantissa = builder.ins().select(mantissa_too_big, halved_mantissa, mantissa);
+    let one_i = builder.ins().iconst(types::I64, 1);
+    let bumped_exponent = builder.ins().iadd(exponent, one_i);
+    let final_exponent_i = builder.ins().select(mantissa_too_big, bumped_exponent, exponent);
+    let final_exponent = builder.ins().fcvt_from_sint(types::F64, final_exponent_i);
+
+    let one = builder.ins().f64const(1.0);
+    let numerator = builder.ins().fsub(reduced_mantissa, one);
+    let denominator = builder.ins().fadd(reduced_mantissa, one);
+    let z = builder.ins().fdiv(numerator, denominator);
+    let z2 = builder.ins().fmul(z, z);
+    let atanh_terms = horner(builder, z2, &[2.0, 2.0 / 3.0, 2.0 / 5.0, 2.0 / 7.0, 2.0 / 9.0]);
+    let ln_mantissa = builder.ins().fmul(atanh_terms, z);
+
+    let ln2 = builder.ins().f64const(std::f64::consts::LN_2);
+    let exponent_term = builder.ins().fmul(final_exponent, ln2);
+
+    builder.ins().fadd(exponent_term, ln_mantissa)
+}
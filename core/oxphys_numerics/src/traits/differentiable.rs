@@ -0,0 +1,367 @@
+use crate::enums::binary_node::BinaryNode;
+use crate::enums::expr::Expr;
+use crate::enums::initialized_expr::InitializedExpr;
+use crate::enums::initialized_leaf::InitializedLeaf;
+use crate::enums::unary_node::UnaryNode;
+use crate::errors::expr_parsing_error::ExprParsingError;
+use crate::traits::expression_node::ExpressionNode;
+
+/// # Differentiable
+/// Symbolic differentiation: given an expression tree, build a new expression tree for its
+/// derivative, so the result can be JIT-compiled with the same machinery as the original
+/// expression instead of falling back to finite differences.
+pub trait Differentiable {
+    /// # Differentiate
+    /// Differentiate this expression with respect to the variable at index `wrt`, applying the
+    /// chain rule recursively over the tree. Returns `Err` for a node shape that doesn't have a
+    /// derivative defined yet - see [`InitializedExpr`]'s impl - rather than panicking, since
+    /// `Comparison`/`Ternary` are reachable, documented node types (added for piecewise
+    /// functions), not a programmer error.
+    fn differentiate(&self, wrt: usize) -> Result<Expr, ExprParsingError>;
+}
+
+impl Differentiable for InitializedLeaf {
+    fn differentiate(&self, wrt: usize) -> Result<Expr, ExprParsingError> {
+        match self {
+            InitializedLeaf::Constant(_) => Ok(constant(0.0)),
+            InitializedLeaf::Variable(variable) => Ok(constant(is_wrt(variable.index(), wrt))),
+            InitializedLeaf::Bound(name_index) => Ok(constant(is_wrt(*name_index, wrt))),
+        }
+    }
+}
+
+impl Differentiable for UnaryNode {
+    fn differentiate(&self, wrt: usize) -> Result<Expr, ExprParsingError> {
+        let inner: Expr = match self {
+            UnaryNode::Negate(inner) => (**inner).clone(),
+            UnaryNode::Sqrt(inner) => (**inner).clone(),
+            UnaryNode::Sin(inner) => (**inner).clone(),
+            UnaryNode::Cos(inner) => (**inner).clone(),
+            UnaryNode::Exp(inner) => (**inner).clone(),
+            UnaryNode::Ln(inner) => (**inner).clone(),
+        };
+        let inner_derivative = inner.differentiate(wrt)?;
+
+        Ok(match self {
+            UnaryNode::Negate(_) => neg(inner_derivative),
+
+            // d/dx sqrt(u) = u' / (2*sqrt(u))
+            UnaryNode::Sqrt(_) => {
+                let two_sqrt_u = mul(constant(2.0), sqrt(inner));
+                div(inner_derivative, two_sqrt_u)
+            }
+
+            // d/dx sin(u) = cos(u) * u'
+            UnaryNode::Sin(_) => mul(cos(inner), inner_derivative),
+
+            // d/dx cos(u) = -sin(u) * u'
+            UnaryNode::Cos(_) => neg(mul(sin(inner), inner_derivative)),
+
+            // d/dx exp(u) = exp(u) * u'
+            UnaryNode::Exp(_) => mul(exp(inner), inner_derivative),
+
+            // d/dx ln(u) = u' / u
+            UnaryNode::Ln(_) => div(inner_derivative, inner),
+        })
+    }
+}
+
+impl Differentiable for BinaryNode {
+    fn differentiate(&self, wrt: usize) -> Result<Expr, ExprParsingError> {
+        Ok(match self {
+            BinaryNode::Add(left, right) => {
+                add(left.differentiate(wrt)?, right.differentiate(wrt)?)
+            }
+
+            BinaryNode::Subtract(left, right) => {
+                sub(left.differentiate(wrt)?, right.differentiate(wrt)?)
+            }
+
+            // Product rule: (f*g)' = f'*g + f*g'
+            BinaryNode::Multiply(left, right) => {
+                let left_term = mul(left.differentiate(wrt)?, (**right).clone());
+                let right_term = mul((**left).clone(), right.differentiate(wrt)?);
+                add(left_term, right_term)
+            }
+
+            // Quotient rule: (f/g)' = (f'*g - f*g') / g^2
+            BinaryNode::Frac(numerator, denominator) => {
+                let new_numerator = sub(
+                    mul(numerator.differentiate(wrt)?, (**denominator).clone()),
+                    mul((**numerator).clone(), denominator.differentiate(wrt)?),
+                );
+                let new_denominator = mul((**denominator).clone(), (**denominator).clone());
+                div(new_numerator, new_denominator)
+            }
+
+            // General power rule: (b^e)' = b^e * (e'*ln(b) + e*b'/b)
+            BinaryNode::Pow(base, exponent) => {
+                let bracket = add(
+                    mul(exponent.differentiate(wrt)?, ln((**base).clone())),
+                    mul(
+                        (**exponent).clone(),
+                        div(base.differentiate(wrt)?, (**base).clone()),
+                    ),
+                );
+                mul(pow((**base).clone(), (**exponent).clone()), bracket)
+            }
+
+            // log_b(x) = ln(x) / ln(b); rewrite and differentiate that instead of special-casing
+            // it, so this stays correct even when `base` itself depends on `wrt`.
+            BinaryNode::Log(base, argument) => {
+                BinaryNode::Frac(Box::new(ln((**argument).clone())), Box::new(ln((**base).clone())))
+                    .differentiate(wrt)?
+            }
+        })
+    }
+}
+
+impl Differentiable for InitializedExpr {
+    fn differentiate(&self, wrt: usize) -> Result<Expr, ExprParsingError> {
+        match self {
+            InitializedExpr::Leaf(leaf) => leaf.differentiate(wrt),
+            InitializedExpr::Unary(unary) => unary.differentiate(wrt),
+            InitializedExpr::Binary(binary) => binary.differentiate(wrt),
+            InitializedExpr::Comparison(_) => Err(ExprParsingError::new_syntax(
+                "differentiating a comparison isn't supported; comparisons are piecewise-constant \
+                 and have a zero derivative everywhere except their (measure-zero) boundary"
+                    .to_string(),
+            )),
+            InitializedExpr::Ternary(_) => Err(ExprParsingError::new_syntax(
+                "differentiating a select isn't supported yet; its gradient depends on which \
+                 branch is active and needs case-based analysis rather than a single chain rule"
+                    .to_string(),
+            )),
+            InitializedExpr::Let { .. } => Err(ExprParsingError::new_syntax(
+                "differentiating a let-binding directly isn't supported yet; differentiate its \
+                 body with the binding inlined instead"
+                    .to_string(),
+            )),
+        }
+    }
+}
+
+impl Differentiable for Expr {
+    fn differentiate(&self, wrt: usize) -> Result<Expr, ExprParsingError> {
+        match self {
+            Expr::Initialized(initialized) => initialized.differentiate(wrt),
+            Expr::Uninitialized(_) => {
+                panic!("cannot differentiate an uninitialized expression; initialize it first")
+            }
+        }
+    }
+}
+
+impl Expr {
+    /// # Derivative
+    /// Named alias for [`Differentiable::differentiate`] matching [`Expr::gradient`]'s naming, so
+    /// callers reach for `expr.derivative(wrt)` instead of importing the `Differentiable` trait
+    /// just to get at a single partial derivative.
+    pub fn derivative(&self, wrt: usize) -> Result<Expr, ExprParsingError> {
+        self.differentiate(wrt)
+    }
+
+    /// # Gradient
+    /// Differentiate this expression with respect to every one of its independent variables, in
+    /// index order, returning the full Jacobian row in one pass. Returns `Err` as soon as any
+    /// partial derivative does - see [`Differentiable::differentiate`].
+    pub fn gradient(&self) -> Result<Vec<Expr>, ExprParsingError> {
+        let num_variables = match self {
+            Expr::Initialized(initialized) => initialized_num_variables(initialized),
+            Expr::Uninitialized(_) => {
+                panic!("cannot differentiate an uninitialized expression; initialize it first")
+            }
+        };
+
+        (0..num_variables).map(|wrt| self.differentiate(wrt)).collect()
+    }
+}
+
+/// # Initialized num variables
+/// Mirror of `Expression::num_variables` for an `InitializedExpr`, used here so `Expr::gradient`
+/// doesn't need to depend on the `Expression` trait just to size its output.
+fn initialized_num_variables(expr: &InitializedExpr) -> usize {
+    use crate::traits::expression::Expression;
+    expr.num_variables()
+}
+
+// --- Simplification helpers -------------------------------------------------------------------
+//
+// Differentiation tends to produce trees cluttered with `*1`, `+0`, and `*0` terms; folding those
+// away as we go keeps the compiled gradient close to what a human would have written by hand.
+
+fn constant(value: f64) -> Expr {
+    InitializedLeaf::Constant(value).to_expr()
+}
+
+fn is_wrt(index: usize, wrt: usize) -> f64 {
+    if index == wrt {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn is_zero(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Initialized(InitializedExpr::Leaf(InitializedLeaf::Constant(value))) if *value == 0.0
+    )
+}
+
+fn is_one(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Initialized(InitializedExpr::Leaf(InitializedLeaf::Constant(value))) if *value == 1.0
+    )
+}
+
+fn add(left: Expr, right: Expr) -> Expr {
+    match (is_zero(&left), is_zero(&right)) {
+        (true, true) => constant(0.0),
+        (true, false) => right,
+        (false, true) => left,
+        (false, false) => BinaryNode::Add(Box::new(left), Box::new(right)).to_expr(true),
+    }
+}
+
+fn sub(left: Expr, right: Expr) -> Expr {
+    match (is_zero(&left), is_zero(&right)) {
+        (_, true) => left,
+        (true, false) => neg(right),
+        (false, false) => BinaryNode::Subtract(Box::new(left), Box::new(right)).to_expr(true),
+    }
+}
+
+fn mul(left: Expr, right: Expr) -> Expr {
+    if is_zero(&left) || is_zero(&right) {
+        return constant(0.0);
+    }
+    if is_one(&left) {
+        return right;
+    }
+    if is_one(&right) {
+        return left;
+    }
+    BinaryNode::Multiply(Box::new(left), Box::new(right)).to_expr(true)
+}
+
+fn div(numerator: Expr, denominator: Expr) -> Expr {
+    if is_zero(&numerator) {
+        return constant(0.0);
+    }
+    BinaryNode::Frac(Box::new(numerator), Box::new(denominator)).to_expr(true)
+}
+
+fn neg(inner: Expr) -> Expr {
+    if is_zero(&inner) {
+        return constant(0.0);
+    }
+    UnaryNode::Negate(Box::new(inner)).to_expr(true)
+}
+
+fn sqrt(inner: Expr) -> Expr {
+    UnaryNode::Sqrt(Box::new(inner)).to_expr(true)
+}
+
+fn sin(inner: Expr) -> Expr {
+    UnaryNode::Sin(Box::new(inner)).to_expr(true)
+}
+
+fn cos(inner: Expr) -> Expr {
+    UnaryNode::Cos(Box::new(inner)).to_expr(true)
+}
+
+fn exp(inner: Expr) -> Expr {
+    UnaryNode::Exp(Box::new(inner)).to_expr(true)
+}
+
+fn ln(inner: Expr) -> Expr {
+    UnaryNode::Ln(Box::new(inner)).to_expr(true)
+}
+
+fn pow(base: Expr, exponent: Expr) -> Expr {
+    BinaryNode::Pow(Box::new(base), Box::new(exponent)).to_expr(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::initialized_variable::InitializedVariable;
+    use crate::structs::uninitialized_variable::UninitializedVariable;
+    use crate::traits::expression::Expression;
+
+    fn eval(expr: &Expr, variables: &Vec<f64>) -> f64 {
+        match expr {
+            Expr::Initialized(initialized) => initialized.evaluate(variables),
+            Expr::Uninitialized(_) => panic!("cannot evaluate an uninitialized expression"),
+        }
+    }
+
+    fn var(index: usize) -> Expr {
+        InitializedLeaf::Variable(InitializedVariable::new(
+            UninitializedVariable::new(format!("var_{index}")),
+            index,
+        ))
+        .to_expr()
+    }
+
+    #[test]
+    fn test_differentiate_constant_is_zero() {
+        let expr = constant(5.0);
+        assert_eq!(eval(&expr.differentiate(0).unwrap(), &vec![1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_differentiate_variable_is_kronecker_delta() {
+        let variables = vec![1.0, 2.0];
+        let x = var(0);
+
+        assert_eq!(eval(&x.differentiate(0).unwrap(), &variables), 1.0);
+        assert_eq!(eval(&x.differentiate(1).unwrap(), &variables), 0.0);
+    }
+
+    #[test]
+    fn test_differentiate_sin() {
+        // d/dx sin(x) = cos(x); at x = 0, cos(0) = 1.
+        let variables = vec![0.0];
+        let expr = sin(var(0));
+        assert_eq!(eval(&expr.differentiate(0).unwrap(), &variables), 1.0);
+    }
+
+    #[test]
+    fn test_differentiate_product_rule() {
+        // d/dx (x * x) = 2x; at x = 3, that's 6.
+        let variables = vec![3.0];
+        let expr = mul(var(0), var(0));
+        assert_eq!(eval(&expr.differentiate(0).unwrap(), &variables), 6.0);
+    }
+
+    #[test]
+    fn test_differentiate_quotient_rule() {
+        // d/dx (1 / x) = -1/x^2; at x = 2, that's -0.25.
+        let variables = vec![2.0];
+        let expr = div(constant(1.0), var(0));
+        assert_eq!(eval(&expr.differentiate(0).unwrap(), &variables), -0.25);
+    }
+
+    #[test]
+    fn test_gradient_returns_one_partial_per_variable() {
+        // f(x, y) = x * y; grad f = (y, x).
+        let variables = vec![3.0, 4.0];
+        let expr = mul(var(0), var(1));
+
+        let gradient = expr.gradient().unwrap();
+        assert_eq!(gradient.len(), 2);
+        assert_eq!(eval(&gradient[0], &variables), 4.0);
+        assert_eq!(eval(&gradient[1], &variables), 3.0);
+    }
+
+    #[test]
+    fn test_differentiate_comparison_is_an_error() {
+        use crate::enums::comparison_node::ComparisonNode;
+
+        let expr = ComparisonNode::Less(Box::new(var(0)), Box::new(constant(1.0))).to_expr(true);
+        assert!(expr.differentiate(0).is_err());
+    }
+}
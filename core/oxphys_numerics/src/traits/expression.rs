@@ -1,26 +1,227 @@
+#[cfg(feature = "jit")]
+use std::any::Any;
+
+#[cfg(feature = "jit")]
+use crate::enums::expr::Expr;
+#[cfg(feature = "jit")]
+use crate::enums::lane_width::LaneWidth;
+#[cfg(feature = "jit")]
+use crate::enums::scalar_width::ScalarWidth;
+#[cfg(feature = "jit")]
 use crate::errors::expr_parsing_error::ExprParsingError;
+#[cfg(feature = "jit")]
 use crate::structs::instruction_set_architecture::InstructionSetArchitecture;
+#[cfg(feature = "jit")]
 use crate::structs::jit_helper::JITHelper;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::condcodes::IntCC;
+#[cfg(feature = "jit")]
 use cranelift_codegen::ir::types;
+#[cfg(feature = "jit")]
 use cranelift_codegen::ir::InstBuilder;
-
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::MemFlags;
+#[cfg(feature = "jit")]
+use cranelift_codegen::ir::{StackSlotData, StackSlotKind};
+#[cfg(feature = "jit")]
+use cranelift_frontend::Variable as CraneliftVariable;
+
+#[cfg(feature = "jit")]
 use super::expression_compiler::ExpressionCompiler;
 
+/// # Compiled function
+/// Owns whatever backend-specific state keeps a JIT-compiled function's executable memory mapped
+/// - a Cranelift `JITModule`, an LLVM `ExecutionEngine` paired with its owning `Context`, or
+/// anything else a future [`crate::backend::Backend`] needs - alongside the bare function pointer
+/// `compile_*` produces. The owner is type-erased behind `Box<dyn Any>` since different backends
+/// keep their JIT memory alive in structurally different ways; it's never downcast, only held so
+/// it isn't dropped (and the memory it owns freed) while `function` might still be called. Generic
+/// over the fn pointer type `F` so one implementation backs every arity; the arity-specific
+/// aliases below (`CompiledExpression1D`, etc.) each get their own inherent `call` method.
+#[cfg(feature = "jit")]
+pub struct CompiledFunction<F> {
+    owner: Box<dyn Any>,
+    function: F,
+}
+
+#[cfg(feature = "jit")]
+impl<F> CompiledFunction<F> {
+    /// # New
+    /// Wrap `function` together with whatever `owner` keeps its executable memory mapped.
+    pub(crate) fn new(owner: impl Any, function: F) -> Self {
+        CompiledFunction {
+            owner: Box::new(owner),
+            function,
+        }
+    }
+}
+
+// Calling `function` only ever reads the JIT-compiled code and the `owner` it's kept alive by,
+// never mutates either, so it's sound for many threads to hold a shared `&CompiledFunction` and
+// call it concurrently (see `parallel::evaluate_vec`).
+#[cfg(feature = "jit")]
+unsafe impl<F> Sync for CompiledFunction<F> {}
+
 /// Type alias for a compiled expression function that maps a single `f64` to a single `f64`.
-pub type CompiledExpression1D = fn(f64) -> f64;
+#[cfg(feature = "jit")]
+pub type CompiledExpression1D = CompiledFunction<fn(f64) -> f64>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpression1D {
+    /// # Call
+    /// Call the compiled function.
+    pub fn call(&self, x: f64) -> f64 {
+        (self.function)(x)
+    }
+}
 
 /// Type alias for a compiled expression function that maps two `f64`s to a single `f64`.
-pub type CompiledExpression2D = fn(f64, f64) -> f64;
+#[cfg(feature = "jit")]
+pub type CompiledExpression2D = CompiledFunction<fn(f64, f64) -> f64>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpression2D {
+    /// # Call
+    /// Call the compiled function.
+    pub fn call(&self, x: f64, y: f64) -> f64 {
+        (self.function)(x, y)
+    }
+}
 
 /// Type alias for a compiled expression function that maps three `f64`s to a single `f64`.
-pub type CompiledExpression3D = fn(f64, f64, f64) -> f64;
+#[cfg(feature = "jit")]
+pub type CompiledExpression3D = CompiledFunction<fn(f64, f64, f64) -> f64>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpression3D {
+    /// # Call
+    /// Call the compiled function.
+    pub fn call(&self, x: f64, y: f64, z: f64) -> f64 {
+        (self.function)(x, y, z)
+    }
+}
 
 /// Type alias for a compiled expression function that maps an array of `f64`s to a single `f64`.
-pub type CompiledExpressionND = fn(*const f64, usize) -> f64;
+#[cfg(feature = "jit")]
+pub type CompiledExpressionND = CompiledFunction<fn(*const f64, usize) -> f64>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpressionND {
+    /// # Call
+    /// Call the compiled function with a pointer to `num_variables` contiguous `f64`s.
+    pub fn call(&self, variables: *const f64, num_variables: usize) -> f64 {
+        (self.function)(variables, num_variables)
+    }
+}
+
+/// Type alias for a compiled expression function that maps a single `f32` to a single `f32`.
+#[cfg(feature = "jit")]
+pub type CompiledExpression1D32 = CompiledFunction<fn(f32) -> f32>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpression1D32 {
+    /// # Call
+    /// Call the compiled function.
+    pub fn call(&self, x: f32) -> f32 {
+        (self.function)(x)
+    }
+}
+
+/// Type alias for a compiled expression function that maps two `f32`s to a single `f32`.
+#[cfg(feature = "jit")]
+pub type CompiledExpression2D32 = CompiledFunction<fn(f32, f32) -> f32>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpression2D32 {
+    /// # Call
+    /// Call the compiled function.
+    pub fn call(&self, x: f32, y: f32) -> f32 {
+        (self.function)(x, y)
+    }
+}
+
+/// Type alias for a compiled expression function that maps three `f32`s to a single `f32`.
+#[cfg(feature = "jit")]
+pub type CompiledExpression3D32 = CompiledFunction<fn(f32, f32, f32) -> f32>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpression3D32 {
+    /// # Call
+    /// Call the compiled function.
+    pub fn call(&self, x: f32, y: f32, z: f32) -> f32 {
+        (self.function)(x, y, z)
+    }
+}
+
+/// Type alias for a compiled expression function that maps an array of `f32`s to a single `f32`,
+/// the `f32` counterpart of [`CompiledExpressionND`]. Packing variables as `f32` halves the memory
+/// each one occupies, letting more of them fit in a cache line or a single SSE/AVX register - worth
+/// it whenever an expression's precision requirements allow it.
+#[cfg(feature = "jit")]
+pub type CompiledExpressionND32 = CompiledFunction<fn(*const f32, usize) -> f32>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpressionND32 {
+    /// # Call
+    /// Call the compiled function with a pointer to `num_variables` contiguous `f32`s.
+    pub fn call(&self, variables: *const f32, num_variables: usize) -> f32 {
+        (self.function)(variables, num_variables)
+    }
+}
+
+/// Type alias for a vectorized compiled expression kernel: given `rows` rows' worth of variables
+/// staged column-major (see [`Expression::compile_nd_vec`]), it writes one output per row into
+/// the `*mut f64` output buffer.
+#[cfg(feature = "jit")]
+pub type CompiledExpressionNDVec = CompiledFunction<fn(*const f64, *mut f64, usize)>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpressionNDVec {
+    /// # Call
+    /// Call the compiled function, writing one output per lane through `out`. `num_variables` is
+    /// passed as the kernel's `cols` argument (see [`Expression::compile_nd_vec`]); the number of
+    /// outputs is fixed by the lane width the kernel was compiled with.
+    pub fn call(&self, variables: *const f64, out: *mut f64, num_variables: usize) {
+        (self.function)(variables, out, num_variables)
+    }
+}
+
+/// Type alias for a batched compiled expression kernel: given `num_rows` rows' worth of
+/// variables staged row-major (row `r`'s `num_vars` values contiguous, see
+/// [`Expression::compile_nd_batch`]), it writes one output per row into the `*mut f64` output
+/// buffer, in a single call.
+#[cfg(feature = "jit")]
+pub type CompiledExpressionNDBatch = CompiledFunction<fn(*const f64, *mut f64, usize, usize)>;
+
+#[cfg(feature = "jit")]
+impl CompiledExpressionNDBatch {
+    /// # Call
+    /// Call the compiled function, evaluating every one of `num_rows` rows (each `num_vars` `f64`s
+    /// wide, row-major) and writing one output per row through `outputs`.
+    pub fn call(&self, inputs: *const f64, outputs: *mut f64, num_rows: usize, num_vars: usize) {
+        (self.function)(inputs, outputs, num_rows, num_vars)
+    }
+}
+
+/// Type alias for a compiled gradient kernel: given a pointer to the input variables and their
+/// count, it writes one partial derivative per variable, in index order, through the `*mut f64`
+/// output buffer (see [`crate::enums::expr::Expr::compile_gradient_nd`]).
+#[cfg(feature = "jit")]
+pub type CompiledGradientND = CompiledFunction<fn(*const f64, *mut f64, usize)>;
+
+#[cfg(feature = "jit")]
+impl CompiledGradientND {
+    /// # Call
+    /// Call the compiled function, writing one partial derivative per variable through `out`.
+    pub fn call(&self, variables: *const f64, out: *mut f64, num_variables: usize) {
+        (self.function)(variables, out, num_variables)
+    }
+}
 
 /// # Expression
 /// This defines everything that we expect from our data structures that represent mathematical
 /// expressions.
+#[cfg(feature = "jit")]
 pub trait Expression: ExpressionCompiler {
     /// # Number of variables
     /// Get the number of independent variables in the expression. This can be easily figured out
@@ -41,6 +242,10 @@ pub trait Expression: ExpressionCompiler {
             return_type,
         );
 
+        // Import the libm transcendentals before taking out the function builder, since both need
+        // a mutable borrow of the JITHelper's function context.
+        let libm = jit_helper.libm_imports();
+
         // Build the function IR.
         {
             // Make the function builder.
@@ -57,15 +262,16 @@ pub trait Expression: ExpressionCompiler {
             let parameter = builder.block_params(entry_block)[0];
 
             // Pass the parameter and the builder to the expression to build itself recursively.
-            let return_value = self.build_jit_1d(&mut builder, parameter);
+            let return_value = self.build_jit_1d(&mut builder, parameter, &libm);
             builder.ins().return_(&[return_value]);
             builder.finalize();
         }
 
-        // Get a callable function pointer.
-        let code = jit_helper.finalize();
-        let compiled_function = unsafe { std::mem::transmute::<_, fn(f64) -> f64>(code) };
-        Ok(compiled_function)
+        // Get a callable function pointer, keeping the module that owns its executable memory
+        // alive alongside it.
+        let (module, code) = jit_helper.finalize();
+        let function = unsafe { std::mem::transmute::<_, fn(f64) -> f64>(code) };
+        Ok(CompiledFunction::new(module, function))
     }
 
     /// # Compile 2D
@@ -82,6 +288,10 @@ pub trait Expression: ExpressionCompiler {
             return_type,
         );
 
+        // Import the libm transcendentals before taking out the function builder, since both need
+        // a mutable borrow of the JITHelper's function context.
+        let libm = jit_helper.libm_imports();
+
         // Build the function IR.
         {
             // Make the function builder.
@@ -99,15 +309,16 @@ pub trait Expression: ExpressionCompiler {
             let y = builder.block_params(entry_block)[1];
 
             // Pass the parameters and the builder to the expression to build itself recursively.
-            let return_value = self.build_jit_2d(&mut builder, x, y);
+            let return_value = self.build_jit_2d(&mut builder, x, y, &libm);
             builder.ins().return_(&[return_value]);
             builder.finalize();
         }
 
-        // Get a callable function pointer.
-        let code = jit_helper.finalize();
-        let compiled_function = unsafe { std::mem::transmute::<_, fn(f64, f64) -> f64>(code) };
-        Ok(compiled_function)
+        // Get a callable function pointer, keeping the module that owns its executable memory
+        // alive alongside it.
+        let (module, code) = jit_helper.finalize();
+        let function = unsafe { std::mem::transmute::<_, fn(f64, f64) -> f64>(code) };
+        Ok(CompiledFunction::new(module, function))
     }
 
     /// # Compile 3D
@@ -124,6 +335,10 @@ pub trait Expression: ExpressionCompiler {
             return_type,
         );
 
+        // Import the libm transcendentals before taking out the function builder, since both need
+        // a mutable borrow of the JITHelper's function context.
+        let libm = jit_helper.libm_imports();
+
         // Build the function IR.
         {
             // Make the function builder.
@@ -142,15 +357,16 @@ pub trait Expression: ExpressionCompiler {
             let z = builder.block_params(entry_block)[2];
 
             // Pass the parameters and the builder to the expression to build itself recursively.
-            let return_value = self.build_jit_3d(&mut builder, x, y, z);
+            let return_value = self.build_jit_3d(&mut builder, x, y, z, &libm);
             builder.ins().return_(&[return_value]);
             builder.finalize();
         }
 
-        // Get a callable function pointer.
-        let code = jit_helper.finalize();
-        let compiled_function = unsafe { std::mem::transmute::<_, fn(f64, f64, f64) -> f64>(code) };
-        Ok(compiled_function)
+        // Get a callable function pointer, keeping the module that owns its executable memory
+        // alive alongside it.
+        let (module, code) = jit_helper.finalize();
+        let function = unsafe { std::mem::transmute::<_, fn(f64, f64, f64) -> f64>(code) };
+        Ok(CompiledFunction::new(module, function))
     }
 
     /// # Compile ND
@@ -167,6 +383,10 @@ pub trait Expression: ExpressionCompiler {
         let return_type = types::F64;
         let mut jit_helper = JITHelper::new(isa, parameters, return_type);
 
+        // Import the libm transcendentals before taking out the function builder, since both need
+        // a mutable borrow of the JITHelper's function context.
+        let libm = jit_helper.libm_imports();
+
         // Build the function IR.
         {
             // Make the function builder.
@@ -188,15 +408,417 @@ pub trait Expression: ExpressionCompiler {
             let parameters = params_slice.to_vec();
 
             // Pass the parameters and the builder to the expression to build itself recursively.
-            let return_value = self.build_jit_nd(&mut builder, &parameters);
+            let return_value =
+                self.build_jit_nd(&mut builder, &parameters, &libm, ScalarWidth::F64);
             builder.ins().return_(&[return_value]);
             builder.finalize();
         }
 
-        // Get a callable function pointer.
-        let code = jit_helper.finalize();
-        let compiled_function =
-            unsafe { std::mem::transmute::<_, fn(*const f64, usize) -> f64>(code) };
-        Ok(compiled_function)
+        // Get a callable function pointer, keeping the module that owns its executable memory
+        // alive alongside it.
+        let (module, code) = jit_helper.finalize();
+        let function = unsafe { std::mem::transmute::<_, fn(*const f64, usize) -> f64>(code) };
+        Ok(CompiledFunction::new(module, function))
+    }
+
+    /// # Compile ND f32
+    /// Just like [`compile_nd`](Self::compile_nd), but the compiled kernel reads and returns
+    /// `f32` rather than `f64`. Worth reaching for whenever an expression's precision requirements
+    /// allow it: packing variables as `f32` halves their footprint, so more of them fit in a cache
+    /// line or a single SSE/AVX register. Drives the same recursive `build_jit_nd` used by
+    /// `compile_nd`, just with [`ScalarWidth::F32`] threaded down instead of the default `F64`.
+    fn compile_nd_f32(&self) -> Result<CompiledExpressionND32, ExprParsingError> {
+        // One of our function's parameters is a pointer. Because these are ISA dependent, start by
+        // making an InstructionSetArchitecture instance for our platform.
+        let isa = InstructionSetArchitecture::current_platform();
+
+        // Prepare our input arguments, then make a JITHelper.
+        let parameters = vec![isa.pointer_type(), types::I64];
+        let return_type = types::F32;
+        let mut jit_helper = JITHelper::new(isa, parameters, return_type);
+
+        // Import the libm transcendentals before taking out the function builder, since both need
+        // a mutable borrow of the JITHelper's function context.
+        let libm = jit_helper.libm_imports();
+
+        // Build the function IR.
+        {
+            // Make the function builder.
+            let mut builder = jit_helper.function_builder();
+
+            // Create the entry block. This is where the function starts, and it has the parameters
+            // that we need.
+            let entry_block = builder.create_block();
+            builder.append_block_params_for_function_params(entry_block);
+            builder.switch_to_block(entry_block);
+            builder.seal_block(entry_block);
+
+            // Get the parameters.
+            let params_slice = builder.block_params(entry_block);
+
+            // Copy them into a standalone vector. This separates the lifetimes of the parameters
+            // from the lifetime of the builder, needed because we used an immutable borrow of the
+            // builder to make the parameters.
+            let parameters = params_slice.to_vec();
+
+            // Pass the parameters and the builder to the expression to build itself recursively,
+            // in f32 rather than compile_nd's f64.
+            let return_value =
+                self.build_jit_nd(&mut builder, &parameters, &libm, ScalarWidth::F32);
+            builder.ins().return_(&[return_value]);
+            builder.finalize();
+        }
+
+        // Get a callable function pointer, keeping the module that owns its executable memory
+        // alive alongside it.
+        let (module, code) = jit_helper.finalize();
+        let function = unsafe { std::mem::transmute::<_, fn(*const f32, usize) -> f32>(code) };
+        Ok(CompiledFunction::new(module, function))
+    }
+
+    /// # Compile ND vec
+    /// Just-in-time compile a vectorized kernel that evaluates `lane_width` rows per call,
+    /// reading `lane_width`-wide chunks of each variable (laid out contiguously, i.e.
+    /// column-major within the chunk) and writing `lane_width` outputs through the `*mut f64`
+    /// parameter. The Python bindings' `PyExpr::evaluate_vec` uses this to process the bulk of a
+    /// large input array, falling back to a scalar ND kernel for whatever tail doesn't fill a full
+    /// chunk; [`Expr::evaluate_vec`](crate::parallel) does not use it.
+    /// Returns `Err` if `self` contains a let-binding: `build_jit_nd_vec` doesn't implement a
+    /// scope stack, so it can't support one yet.
+    fn compile_nd_vec(
+        &self,
+        lane_width: LaneWidth,
+    ) -> Result<CompiledExpressionNDVec, ExprParsingError> {
+        // `build_jit_nd_vec` doesn't support scoped variables yet, so reject a let-binding up
+        // front with a typed error rather than letting it panic deep inside codegen.
+        if self.contains_let() {
+            return Err(ExprParsingError::new_syntax(
+                "let-bindings are not yet supported by compile_nd_vec/compile_nd_batch: the \
+                 vectorized build_jit_nd_vec path doesn't implement a scope stack. Use \
+                 Expression::compile_nd instead, which walks the tree directly and already \
+                 supports let-bindings."
+                    .to_string(),
+            ));
+        }
+
+        // One of our function's parameters is a pointer. Because these are ISA dependent, start by
+        // making an InstructionSetArchitecture instance for our platform.
+        let isa = InstructionSetArchitecture::current_platform();
+        let lanes = lane_width.cranelift_type();
+
+        // Prepare our input arguments, then make a JITHelper. The function writes its result
+        // through the output pointer rather than returning it, since a vector can't be returned
+        // through the same ABI slot as the scalar `compile_nd` kernels.
+        let parameters = vec![isa.pointer_type(), isa.pointer_type(), types::I64];
+        let mut jit_helper = JITHelper::new_void(isa, parameters);
+
+        // Import the libm transcendentals before taking out the function builder, since both need
+        // a mutable borrow of the JITHelper's function context.
+        let libm = jit_helper.libm_imports();
+
+        // Build the function IR.
+        {
+            // Make the function builder.
+            let mut builder = jit_helper.function_builder();
+
+            // Create the entry block. This is where the function starts, and it has the parameters
+            // that we need.
+            let entry_block = builder.create_block();
+            builder.append_block_params_for_function_params(entry_block);
+            builder.switch_to_block(entry_block);
+            builder.seal_block(entry_block);
+
+            // Get the parameters.
+            let params_slice = builder.block_params(entry_block);
+            let args_ptr = params_slice[0];
+            let out_ptr = params_slice[1];
+            let cols = params_slice[2];
+
+            // Pass the parameters and the builder to the expression to build itself recursively.
+            let return_value =
+                self.build_jit_nd_vec(&mut builder, &[args_ptr, cols], &libm, lanes);
+            builder
+                .ins()
+                .store(MemFlags::new(), return_value, out_ptr, 0);
+            builder.ins().return_(&[]);
+            builder.finalize();
+        }
+
+        // Get a callable function pointer, keeping the module that owns its executable memory
+        // alive alongside it.
+        let (module, code) = jit_helper.finalize();
+        let function = unsafe { std::mem::transmute::<_, fn(*const f64, *mut f64, usize)>(code) };
+        Ok(CompiledFunction::new(module, function))
+    }
+
+    /// # Compile ND batch
+    /// Just-in-time compile a self-contained kernel that evaluates every row of a whole dataset in
+    /// one call: `inputs` holds `num_rows` rows of `self.num_variables()` `f64`s each, laid out
+    /// row-major (unlike [`compile_nd_vec`](Self::compile_nd_vec)'s column-major chunks), and one
+    /// output per row is written through `outputs`. Internally this emits a real Cranelift loop
+    /// (row count is only known at call time, so it can't be unrolled like the lane-wise fallbacks
+    /// elsewhere in this crate): a vectorized loop processes `lane_width` rows per iteration via
+    /// [`ExpressionCompiler::build_jit_nd_vec`], transposing each chunk's rows into a column-major
+    /// stack slot first since that's the layout `build_jit_nd_vec`'s `Variable` leaf expects, then
+    /// a scalar tail loop handles whatever doesn't fill a full chunk via
+    /// [`ExpressionCompiler::build_jit_nd`] directly against the row-major buffer (a row's
+    /// variables are already contiguous, exactly what `build_jit_nd`'s `Variable` leaf wants).
+    /// Returns `Err` if `self` contains a let-binding: `build_jit_nd_vec` doesn't implement a
+    /// scope stack, so the vectorized chunk loop can't support one yet.
+    fn compile_nd_batch(
+        &self,
+        lane_width: LaneWidth,
+    ) -> Result<CompiledExpressionNDBatch, ExprParsingError> {
+        // Same restriction as `compile_nd_vec`: the vectorized chunk loop below is built with
+        // `build_jit_nd_vec`, which doesn't support scoped variables yet.
+        if self.contains_let() {
+            return Err(ExprParsingError::new_syntax(
+                "let-bindings are not yet supported by compile_nd_batch: its vectorized chunk \
+                 loop is built with build_jit_nd_vec, which doesn't implement a scope stack. Use \
+                 Expression::compile_nd instead, which walks the tree directly and already \
+                 supports let-bindings."
+                    .to_string(),
+            ));
+        }
+
+        let isa = InstructionSetArchitecture::current_platform();
+        let lanes = lane_width.lanes();
+        let vector_type = lane_width.cranelift_type();
+        let num_variables = self.num_variables();
+
+        // Row-major stride, in bytes, between the start of one row's variables and the next.
+        let row_stride_bytes = (num_variables * 8) as i64;
+
+        // A dedicated scope slot for the loop induction variable, picked past every index a
+        // `Let`-binding inside `self` could use: `InitializedExpr::num_variables` already folds
+        // `name_index + 1` into its result, so every `Let`'s `name_index` is strictly less than
+        // `num_variables`, and this index can never collide with one.
+        let row_index_var = CraneliftVariable::new(num_variables);
+
+        let parameters = vec![isa.pointer_type(), isa.pointer_type(), types::I64, types::I64];
+        let mut jit_helper = JITHelper::new_void(isa, parameters);
+        let libm = jit_helper.libm_imports();
+
+        {
+            let mut builder = jit_helper.function_builder();
+
+            let entry_block = builder.create_block();
+            builder.append_block_params_for_function_params(entry_block);
+            builder.switch_to_block(entry_block);
+            builder.seal_block(entry_block);
+
+            let params_slice = builder.block_params(entry_block);
+            let inputs_ptr = params_slice[0];
+            let outputs_ptr = params_slice[1];
+            let num_rows = params_slice[2];
+
+            // A column-major scratch buffer, sized for one chunk of `lanes` rows: exactly the
+            // layout `build_jit_nd_vec`'s `Variable` leaf reads from.
+            let chunk_bytes = (num_variables * lanes * 8) as u32;
+            let stack_slot = builder.create_sized_stack_slot(StackSlotData::new(
+                StackSlotKind::ExplicitSlot,
+                chunk_bytes,
+                3, // 8-byte aligned.
+            ));
+            let scratch_ptr = builder.ins().stack_addr(isa.pointer_type(), stack_slot, 0);
+
+            builder.declare_var(row_index_var, types::I64);
+            let zero = builder.ins().iconst(types::I64, 0);
+            builder.def_var(row_index_var, zero);
+
+            let vector_header_block = builder.create_block();
+            let vector_body_block = builder.create_block();
+            let scalar_header_block = builder.create_block();
+            let scalar_body_block = builder.create_block();
+            let final_block = builder.create_block();
+
+            builder.ins().jump(vector_header_block, &[]);
+
+            // Vectorized loop: while at least `lanes` rows remain, transpose them into the
+            // scratch buffer and evaluate the whole chunk with one `build_jit_nd_vec` call.
+            builder.switch_to_block(vector_header_block);
+            let row_index = builder.use_var(row_index_var);
+            let lanes_const = builder.ins().iconst(types::I64, lanes as i64);
+            let rows_left = builder.ins().isub(num_rows, row_index);
+            let has_full_chunk =
+                builder
+                    .ins()
+                    .icmp(IntCC::SignedGreaterThanOrEqual, rows_left, lanes_const);
+            builder
+                .ins()
+                .brif(has_full_chunk, vector_body_block, &[], scalar_header_block, &[]);
+
+            builder.switch_to_block(vector_body_block);
+            builder.seal_block(vector_body_block);
+            for variable_index in 0..num_variables {
+                for lane in 0..lanes {
+                    let lane_row = if lane == 0 {
+                        row_index
+                    } else {
+                        let lane_offset = builder.ins().iconst(types::I64, lane as i64);
+                        builder.ins().iadd(row_index, lane_offset)
+                    };
+                    let row_byte_offset = builder.ins().imul_imm(lane_row, row_stride_bytes);
+                    let row_addr = builder.ins().iadd(inputs_ptr, row_byte_offset);
+                    let value = builder.ins().load(
+                        types::F64,
+                        MemFlags::new(),
+                        row_addr,
+                        (variable_index * 8) as i32,
+                    );
+                    let scratch_offset = (variable_index * lanes + lane) * 8;
+                    builder
+                        .ins()
+                        .stack_store(value, stack_slot, scratch_offset as i32);
+                }
+            }
+            // `build_jit_nd_vec`'s `Variable` leaf only ever reads `parameters[0]`; this second
+            // slot just mirrors `compile_nd_vec`'s own `cols` parameter for signature symmetry.
+            let cols_value = builder.ins().iconst(types::I64, num_variables as i64);
+            let chunk_result = self.build_jit_nd_vec(
+                &mut builder,
+                &[scratch_ptr, cols_value],
+                &libm,
+                vector_type,
+            );
+            let out_byte_offset = builder.ins().imul_imm(row_index, 8);
+            let out_addr = builder.ins().iadd(outputs_ptr, out_byte_offset);
+            builder
+                .ins()
+                .store(MemFlags::new(), chunk_result, out_addr, 0);
+            let next_row_index = builder.ins().iadd(row_index, lanes_const);
+            builder.def_var(row_index_var, next_row_index);
+            builder.ins().jump(vector_header_block, &[]);
+            builder.seal_block(vector_header_block);
+
+            // Scalar tail loop: evaluate whatever didn't fill a full vector chunk one row at a
+            // time, reading each row directly out of the row-major buffer.
+            builder.switch_to_block(scalar_header_block);
+            let row_index = builder.use_var(row_index_var);
+            let has_tail_row = builder
+                .ins()
+                .icmp(IntCC::SignedLessThan, row_index, num_rows);
+            builder
+                .ins()
+                .brif(has_tail_row, scalar_body_block, &[], final_block, &[]);
+
+            builder.switch_to_block(scalar_body_block);
+            builder.seal_block(scalar_body_block);
+            let row_byte_offset = builder.ins().imul_imm(row_index, row_stride_bytes);
+            let row_addr = builder.ins().iadd(inputs_ptr, row_byte_offset);
+            let scalar_result =
+                self.build_jit_nd(&mut builder, &[row_addr], &libm, ScalarWidth::F64);
+            let out_byte_offset = builder.ins().imul_imm(row_index, 8);
+            let out_addr = builder.ins().iadd(outputs_ptr, out_byte_offset);
+            builder
+                .ins()
+                .store(MemFlags::new(), scalar_result, out_addr, 0);
+            let one = builder.ins().iconst(types::I64, 1);
+            let next_row_index = builder.ins().iadd(row_index, one);
+            builder.def_var(row_index_var, next_row_index);
+            builder.ins().jump(scalar_header_block, &[]);
+            builder.seal_block(scalar_header_block);
+
+            builder.switch_to_block(final_block);
+            builder.seal_block(final_block);
+            builder.ins().return_(&[]);
+            builder.finalize();
+        }
+
+        let (module, code) = jit_helper.finalize();
+        let function =
+            unsafe { std::mem::transmute::<_, fn(*const f64, *mut f64, usize, usize)>(code) };
+        Ok(CompiledFunction::new(module, function))
+    }
+}
+
+#[cfg(feature = "jit")]
+impl Expr {
+    /// # Compile ND
+    /// Dispatch through to [`Expression::compile_nd`], for callers - namely
+    /// [`Expr::evaluate_vec`](crate::parallel) - that hold an `Expr` rather than an
+    /// `InitializedExpr` and so can't call the trait method directly (`Expr` itself doesn't
+    /// implement `Expression`). Mirrors [`Expr::compile_nd_cse`](crate::dag)'s entry-point shape.
+    pub fn compile_nd(&self) -> Result<CompiledExpressionND, ExprParsingError> {
+        match self {
+            Expr::Initialized(initialized) => initialized.compile_nd(),
+            Expr::Uninitialized(_) => {
+                panic!("cannot compile an uninitialized expression; initialize it first")
+            }
+        }
+    }
+}
+
+/// # Expression
+/// The interpreter-only surface of [`Expression`], available with `--no-default-features` (i.e.
+/// without the `jit` feature). Evaluation still goes through each node's own `evaluate` method;
+/// only the Cranelift-backed `compile_*` family is unavailable here, since it requires executable
+/// memory that isn't present on every target (wasm, locked-down embedded).
+#[cfg(not(feature = "jit"))]
+pub trait Expression {
+    /// # Number of variables
+    /// Get the number of independent variables in the expression. This can be easily figured out
+    /// by the maximum index of the variables in the expression.
+    fn num_variables(&self) -> usize;
+}
+
+#[cfg(all(test, feature = "jit"))]
+mod tests {
+    use super::*;
+    use crate::enums::binary_node::BinaryNode;
+    use crate::enums::expr::Expr;
+    use crate::enums::initialized_expr::InitializedExpr;
+    use crate::enums::initialized_leaf::InitializedLeaf;
+    use crate::structs::initialized_variable::InitializedVariable;
+    use crate::structs::uninitialized_variable::UninitializedVariable;
+    use crate::traits::expression_node::ExpressionNode;
+
+    fn var(index: usize) -> Expr {
+        InitializedLeaf::Variable(InitializedVariable::new(
+            UninitializedVariable::new(format!("var_{index}")),
+            index,
+        ))
+        .to_expr()
+    }
+
+    #[test]
+    fn test_compile_nd_vec_two_lanes() {
+        // f(x, y) = x + y, evaluated across a pair of lanes in one call.
+        let expr = BinaryNode::Add(Box::new(var(0)), Box::new(var(1)));
+        let f = expr.compile_nd_vec(LaneWidth::Two).unwrap();
+
+        // Column-major: variable 0's two lanes, then variable 1's two lanes.
+        let variables = vec![1.0, 2.0, 10.0, 20.0];
+        let mut out = vec![0.0; 2];
+        f.call(variables.as_ptr(), out.as_mut_ptr(), 2);
+
+        assert_eq!(out, vec![11.0, 22.0]);
+    }
+
+    #[test]
+    fn test_compile_nd_batch_with_vector_chunk_and_scalar_tail() {
+        // f(x, y) = x + y, evaluated over five rows in one call: two lanes' worth of vectorized
+        // chunks plus a one-row scalar tail.
+        let expr = BinaryNode::Add(Box::new(var(0)), Box::new(var(1)));
+        let f = expr.compile_nd_batch(LaneWidth::Two).unwrap();
+
+        // Row-major: each row is (x, y).
+        let inputs = vec![1.0, 10.0, 2.0, 20.0, 3.0, 30.0, 4.0, 40.0, 5.0, 50.0];
+        let mut out = vec![0.0; 5];
+        f.call(inputs.as_ptr(), out.as_mut_ptr(), 5, 2);
+
+        assert_eq!(out, vec![11.0, 22.0, 33.0, 44.0, 55.0]);
+    }
+
+    #[test]
+    fn test_compile_nd_batch_rejects_let_binding() {
+        // let y = x in y + y; build_jit_nd_vec can't express a let's scope yet, so compile_nd_batch
+        // should return a typed error rather than panicking inside codegen.
+        let bound_y = InitializedLeaf::Bound(0).to_expr();
+        let body = BinaryNode::Add(Box::new(bound_y.clone()), Box::new(bound_y));
+        let expr = InitializedExpr::let_binding(0, var(0), body.to_expr(true));
+
+        assert!(expr.compile_nd_batch(LaneWidth::Two).is_err());
+        assert!(expr.compile_nd_vec(LaneWidth::Two).is_err());
     }
 }
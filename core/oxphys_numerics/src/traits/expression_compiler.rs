@@ -0,0 +1,109 @@
+#![cfg(feature = "jit")]
+
+use cranelift_codegen::ir::types;
+use cranelift_codegen::ir::Value;
+use cranelift_frontend::FunctionBuilder;
+
+use crate::enums::expr::Expr;
+use crate::enums::scalar_width::ScalarWidth;
+use crate::structs::libm_imports::LibmImports;
+
+/// # Expression compiler
+/// This trait defines all the methods that need to be implemented to make an it possible to
+/// jit-compile an expression. Gated behind the `jit` feature: everything in here pulls in
+/// `cranelift-codegen`/`cranelift-frontend`, which isn't available on every target (wasm, locked-
+/// down embedded) and isn't needed by consumers who only call each node's `evaluate`.
+pub(crate) trait ExpressionCompiler {
+    /// # Build jit 1D
+    /// Given a jit function builder, add this expression to the function builder. This is called
+    /// when we build an expression that takes a single f64 input. `libm` carries the `FuncRef`s
+    /// for the transcendental functions that don't have a hardware instruction (`sin`/`cos`/
+    /// `exp`/`ln`), so nodes that need one can emit a call to it.
+    fn build_jit_1d(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameter: Value,
+        libm: &LibmImports,
+    ) -> Value;
+
+    /// # Build jit 2D
+    /// Given a jit function builder, add this expression to the function builder. This is called
+    /// when we build an expression that takes two f64 inputs.
+    fn build_jit_2d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        libm: &LibmImports,
+    ) -> Value;
+
+    /// # Build jit 3D
+    /// Given a jit function builder, add this expression to the function builder. This is called
+    /// when we build an expression that takes three f64 inputs.
+    fn build_jit_3d(
+        &self,
+        builder: &mut FunctionBuilder,
+        param_0: Value,
+        param_1: Value,
+        param_2: Value,
+        libm: &LibmImports,
+    ) -> Value;
+
+    /// # Build jit ND
+    /// Given a jit function builder, add this expression to the function builder. This is called
+    /// when we build an expression that takes an ND input (i.e. an array of `width`-precision
+    /// floats). `libm` carries the `FuncRef`s for the transcendental functions that don't have a
+    /// hardware instruction (`sin`/`cos`/`exp`/`ln`), so nodes that need one can emit a call to
+    /// it. `width` picks the precision every constant/load this call emits is built at - see
+    /// [`crate::traits::expression::Expression::compile_nd_f32`] for the `F32` entry point.
+    fn build_jit_nd(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        width: ScalarWidth,
+    ) -> Value;
+
+    /// # Build jit ND vec
+    /// Like [`build_jit_nd`](Self::build_jit_nd), but builds a vectorized kernel that evaluates
+    /// `lanes` independent rows at once. `parameters` holds the same `(*const f64, i64)` pair as
+    /// `build_jit_nd`, except `LeafNode::Variable`/`InitializedLeaf::Variable` load a
+    /// `lanes`-wide chunk instead of a single `f64`: the caller is responsible for staging the
+    /// input rows column-major (one variable's values across all `lanes` rows laid out
+    /// contiguously) so that load is a single vector `load`, not a gather.
+    fn build_jit_nd_vec(
+        &self,
+        builder: &mut FunctionBuilder,
+        parameters: &[Value],
+        libm: &LibmImports,
+        lanes: types::Type,
+    ) -> Value;
+
+    /// # Contains let binding
+    /// Whether this expression (or any of its children) contains an `InitializedExpr::Let`.
+    /// [`Expression::compile_nd_vec`](crate::traits::expression::Expression::compile_nd_vec)/
+    /// `compile_nd_batch` call this before emitting any Cranelift IR, since `build_jit_nd_vec`
+    /// doesn't support scoped variables yet - that way an expression with a let-binding gets a
+    /// typed [`crate::errors::expr_parsing_error::ExprParsingError`] instead of panicking deep
+    /// inside codegen. Defaults to `false`; only `InitializedExpr` (the only place a `Let` can
+    /// actually appear) and the composite nodes that hold `Box<Expr>` children override it to
+    /// recurse.
+    fn contains_let(&self) -> bool {
+        false
+    }
+}
+
+impl Expr {
+    /// # Contains let binding
+    /// Dispatches through to [`ExpressionCompiler::contains_let`], for callers - namely
+    /// [`Expr::evaluate_vec`](crate::parallel) - that hold an `Expr` rather than an
+    /// `InitializedExpr` and so can't call the trait method directly (`Expr` itself doesn't
+    /// implement `ExpressionCompiler`). An uninitialized expression can't contain a resolved
+    /// `Let` binding, so this is always `false` for `Expr::Uninitialized`.
+    pub(crate) fn contains_let(&self) -> bool {
+        match self {
+            Expr::Initialized(initialized) => initialized.contains_let(),
+            Expr::Uninitialized(_) => false,
+        }
+    }
+}
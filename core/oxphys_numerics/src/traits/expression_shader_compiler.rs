@@ -0,0 +1,136 @@
+#![cfg(feature = "gpu")]
+
+use crate::enums::expr::Expr;
+use crate::traits::expression::Expression;
+
+/// # Expression shader compiler
+/// This trait defines the method that needs to be implemented to lower an expression to
+/// GPU-executable shader source. Gated behind the `gpu` feature, the way
+/// [`crate::traits::expression_compiler::ExpressionCompiler`] is gated behind `jit`: this is a
+/// source-emitting backend, not a Cranelift one, but it's built the same way - each node
+/// recursively stringifies its children and wraps the result in its own operator.
+pub trait ExpressionShaderCompiler: Expression {
+    /// # Wgsl expr
+    /// Emit the WGSL scalar expression computing this node's value, recursing into its children.
+    /// `buffer` names the `array<f32>` storage buffer the kernel's `Variable` leaves index into -
+    /// see [`crate::enums::initialized_leaf::InitializedLeaf::Variable`]'s impl, which reuses
+    /// `Variable::index` the same way `ExpressionCompiler::build_jit_nd`'s `Variable` leaf reuses
+    /// it to compute a byte offset.
+    fn wgsl_expr(&self, buffer: &str) -> String;
+
+    /// # Emit wgsl
+    /// Lower this expression to a complete WGSL compute kernel: one invocation per row of a
+    /// `num_variables()`-wide input buffer, writing a single `f32` result per row to the output
+    /// buffer. Mirrors [`crate::traits::expression::Expression::compile_nd`] - the same "one
+    /// input row in, one scalar out" contract - except the kernel is shader *source text* for a
+    /// GPU to compile and dispatch, rather than executable machine code for this CPU to call
+    /// directly. A parameter-sweep caller stages `num_variables()` `f32`s per row, contiguously,
+    /// across as many rows as there are invocations.
+    fn emit_wgsl(&self) -> String {
+        let num_variables = self.num_variables().max(1);
+        let body = self.wgsl_expr("input");
+
+        format!(
+            "@group(0) @binding(0) var<storage, read> input: array<f32>;\n\
+             @group(0) @binding(1) var<storage, read_write> output: array<f32>;\n\
+             \n\
+             const NUM_VARIABLES: u32 = {num_variables}u;\n\
+             \n\
+             @compute @workgroup_size(64)\n\
+             fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {{\n\
+             \x20   let row = global_id.x;\n\
+             \x20   if (row * NUM_VARIABLES >= arrayLength(&input)) {{\n\
+             \x20       return;\n\
+             \x20   }}\n\
+             \x20   output[row] = {body};\n\
+             }}\n"
+        )
+    }
+}
+
+impl Expr {
+    /// # Emit wgsl
+    /// Lower this expression to a complete WGSL compute kernel - see
+    /// [`ExpressionShaderCompiler::emit_wgsl`] - dispatching through whichever concrete node type
+    /// `self` currently holds. This is the entry point callers reach for, since
+    /// `ExpressionShaderCompiler` itself is implemented per node type rather than for `Expr`.
+    pub fn emit_wgsl(&self) -> String {
+        match self {
+            Expr::Initialized(initialized) => initialized.emit_wgsl(),
+            Expr::Uninitialized(_) => {
+                panic!("cannot emit WGSL for an uninitialized expression; initialize it first")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::binary_node::BinaryNode;
+    use crate::enums::comparison_node::ComparisonNode;
+    use crate::enums::initialized_leaf::InitializedLeaf;
+    use crate::enums::ternary_node::TernaryNode;
+    use crate::enums::unary_node::UnaryNode;
+    use crate::structs::initialized_variable::InitializedVariable;
+    use crate::structs::uninitialized_variable::UninitializedVariable;
+    use crate::traits::expression_node::ExpressionNode;
+
+    fn var(index: usize) -> Expr {
+        InitializedLeaf::Variable(InitializedVariable::new(
+            UninitializedVariable::new(format!("var_{index}")),
+            index,
+        ))
+        .to_expr()
+    }
+
+    #[test]
+    fn test_wgsl_expr_leaf() {
+        let constant = InitializedLeaf::Constant(2.0).to_expr();
+        assert!(constant.emit_wgsl().contains("2.0"));
+
+        assert!(var(0)
+            .emit_wgsl()
+            .contains("input[row * NUM_VARIABLES + 0u]"));
+    }
+
+    #[test]
+    fn test_wgsl_expr_unary() {
+        let expr = UnaryNode::Sin(Box::new(var(0))).to_expr(true);
+        assert!(expr
+            .emit_wgsl()
+            .contains("sin(input[row * NUM_VARIABLES + 0u])"));
+    }
+
+    #[test]
+    fn test_wgsl_expr_binary() {
+        let expr = BinaryNode::Add(Box::new(var(0)), Box::new(var(1))).to_expr(true);
+        assert!(expr.emit_wgsl().contains(
+            "(input[row * NUM_VARIABLES + 0u] + input[row * NUM_VARIABLES + 1u])"
+        ));
+    }
+
+    #[test]
+    fn test_wgsl_expr_comparison() {
+        let expr = ComparisonNode::Less(Box::new(var(0)), Box::new(var(1))).to_expr(true);
+        assert!(expr.emit_wgsl().contains("select(0.0, 1.0,"));
+    }
+
+    #[test]
+    fn test_wgsl_expr_ternary() {
+        let cond = ComparisonNode::Less(Box::new(var(0)), Box::new(var(1))).to_expr(true);
+        let expr =
+            TernaryNode::Select(Box::new(cond), Box::new(var(0)), Box::new(var(1))).to_expr(true);
+        let wgsl = expr.emit_wgsl();
+        assert!(wgsl.contains("select("));
+    }
+
+    #[test]
+    fn test_emit_wgsl_wraps_a_complete_kernel() {
+        let expr = BinaryNode::Add(Box::new(var(0)), Box::new(var(1))).to_expr(true);
+        let kernel = expr.emit_wgsl();
+
+        assert!(kernel.contains("@compute @workgroup_size(64)"));
+        assert!(kernel.contains("const NUM_VARIABLES: u32 = 2u;"));
+    }
+}
@@ -1,41 +1,69 @@
 use numpy::{
-    ndarray::{self, s, Dim},
-    PyArray, PyArray1, PyArrayMethods, PyReadonlyArray2,
+    ndarray::{self, s, ArrayView1, Dim},
+    PyArray, PyArray1, PyArrayMethods, PyReadonlyArray1, PyReadonlyArray2,
 };
 use pyo3::{prelude::*, IntoPyObjectExt};
 
 use oxphys_numerics::{
-    enums::{binary_node::BinaryNode, expr::Expr, leaf_node::LeafNode, unary_node::UnaryNode},
-    traits::expression::Expression,
+    enums::{
+        binary_node::BinaryNode, expr::Expr, initialized_expr::InitializedExpr,
+        initialized_leaf::InitializedLeaf, lane_width::LaneWidth, unary_node::UnaryNode,
+    },
+    structs::{
+        initialized_variable::InitializedVariable, uninitialized_variable::UninitializedVariable,
+    },
+    traits::{
+        expression::{CompiledExpressionND, CompiledExpressionNDVec, Expression},
+        expression_node::ExpressionNode,
+    },
 };
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+use rayon::slice::ParallelSliceMut;
+
+/// The lane width `evaluate_vec` vectorizes with when a caller opts in. `LaneWidth::Four` matches
+/// the widest vector register common to the platforms we JIT for (AVX-class x86, NEON-pair-wide
+/// Arm), so it's the best default until we expose lane width as its own knob.
+const VEC_LANE_WIDTH: LaneWidth = LaneWidth::Four;
 
 #[pyclass]
-#[derive(Debug, Clone)]
 pub struct PyExpr {
     pub inner: Expr,
-    compiled_fn: Option<fn(*const f64, usize) -> f64>,
+    compiled_fn: Option<CompiledExpressionND>,
+    compiled_fn_vec: Option<CompiledExpressionNDVec>,
 }
 
 #[pymethods]
 impl PyExpr {
+    /// # Evaluate
+    /// Compile the expression and evaluate it once against a single row of variables. Mirrors
+    /// `evaluate_vec`, but for a single 1-D NumPy array instead of a 2-D batch.
+    pub fn evaluate(&mut self, variables: PyReadonlyArray1<f64>) -> PyResult<f64> {
+        let f = ensure_compiled_nd(&self.inner, &mut self.compiled_fn);
+        let variables_array = variables.as_array();
+        let cols = variables_array.len();
+        Ok(evaluate_row(f, variables_array, cols))
+    }
+
     /// # Evaluate vec
-    /// Compile the expression and pass in a NumPy array of values to evaluate the expression.
+    /// Compile the expression and pass in a NumPy array of values to evaluate the expression. If
+    /// `vectorize` is set, the bulk of the array is evaluated `VEC_LANE_WIDTH` rows at a time
+    /// through the SIMD kernel, falling back to the scalar kernel for whatever tail doesn't fill
+    /// a full lane-wide block.
+    ///
+    /// `variables` doesn't need to be C-contiguous: a transposed array, or a broadcast view
+    /// produced by `np.broadcast_to`, both report non-unit row strides, and each such row is
+    /// gathered into a small contiguous scratch buffer before being handed to the kernel rather
+    /// than read straight off the array's backing memory.
     pub fn evaluate_vec<'py>(
         &mut self,
         py: Python<'py>,
         variables: PyReadonlyArray2<f64>,
         parallel: bool,
+        vectorize: bool,
     ) -> PyResult<Bound<'py, PyArray<f64, Dim<[usize; 1]>>>> {
-        // Jit-compile the expression, if it hasn't been compiled yet.
-        let f = match self.compiled_fn {
-            Some(f) => f,
-            None => {
-                let f = self.inner.compile_nd().unwrap();
-                self.compiled_fn = Some(f);
-                f
-            }
-        };
+        // Jit-compile the scalar expression, if it hasn't been compiled yet. We always need this,
+        // since it's also the tail fallback for the vectorized path.
+        let f = ensure_compiled_nd(&self.inner, &mut self.compiled_fn);
 
         // Get the number of rows and columns in the input array.
         let dims = variables.dims();
@@ -53,18 +81,58 @@ impl PyExpr {
         // a PyArray in a multi-threaded context.
         let variables_array = variables.as_array();
 
-        if parallel {
+        if vectorize {
+            // Jit-compile the vectorized kernel, if it hasn't been compiled yet.
+            let f_vec = ensure_compiled_nd_vec(&self.inner, &mut self.compiled_fn_vec);
+
+            let lanes = VEC_LANE_WIDTH.lanes();
+            let block_rows = (rows / lanes) * lanes;
+            let (block_output, tail_output) = output.split_at_mut(block_rows);
+
+            // Evaluate one lane-wide block of rows. The vector kernel expects each variable's
+            // values for the whole block laid out contiguously, so stage that layout here before
+            // calling in.
+            let evaluate_block = |block_index: usize, block_out: &mut [f64]| {
+                let row_start = block_index * lanes;
+                let mut staged = vec![0.0; cols * lanes];
+                for lane in 0..lanes {
+                    let row = variables_array.slice(s![row_start + lane, ..]);
+                    for (col, value) in row.iter().enumerate() {
+                        staged[col * lanes + lane] = *value;
+                    }
+                }
+                f_vec.call(staged.as_ptr(), block_out.as_mut_ptr(), cols);
+            };
+
+            if parallel {
+                block_output
+                    .par_chunks_mut(lanes)
+                    .enumerate()
+                    .for_each(evaluate_block);
+            } else {
+                block_output
+                    .chunks_mut(lanes)
+                    .enumerate()
+                    .for_each(evaluate_block);
+            }
+
+            // Fall back to the scalar kernel for whatever tail doesn't fill a full block.
+            tail_output.iter_mut().enumerate().for_each(|(i, value)| {
+                let row = variables_array.slice(s![block_rows + i, ..]);
+                *value = evaluate_row(f, row, cols);
+            });
+        } else if parallel {
             // If we're running in parallel, we can use Rayon to parallelize the evaluation.
             output.par_iter_mut().enumerate().for_each(|(i, value)| {
                 // Create slice without referencing PyArray. Instead, we use the variables_array,
                 // which can be safely shared between threads.
-                let row_slice = variables_array.slice(s![i, ..]);
-                *value = f(row_slice.as_ptr(), cols);
+                let row = variables_array.slice(s![i, ..]);
+                *value = evaluate_row(f, row, cols);
             });
         } else {
             output.iter_mut().enumerate().for_each(|(i, value)| {
-                let values = variables_array.slice(s![i, ..]);
-                *value = f(values.as_ptr(), cols);
+                let row = variables_array.slice(s![i, ..]);
+                *value = evaluate_row(f, row, cols);
             });
         }
 
@@ -78,16 +146,22 @@ impl PyExpr {
     #[staticmethod]
     pub fn constant(value: f64) -> Self {
         PyExpr {
-            inner: Expr::Leaf(LeafNode::Constant(value)),
+            inner: InitializedLeaf::Constant(value).to_expr(),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn variable(index: usize) -> Self {
         PyExpr {
-            inner: Expr::Leaf(LeafNode::Variable(index)),
+            inner: InitializedLeaf::Variable(InitializedVariable::new(
+                UninitializedVariable::new(format!("var_{index}")),
+                index,
+            ))
+            .to_expr(),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
@@ -95,48 +169,54 @@ impl PyExpr {
     #[staticmethod]
     pub fn negate(child: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Unary(UnaryNode::Negate(Box::new(child.inner.clone()))),
+            inner: UnaryNode::Negate(Box::new(child.inner.clone())).to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn sqrt(child: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Unary(UnaryNode::Sqrt(Box::new(child.inner.clone()))),
+            inner: UnaryNode::Sqrt(Box::new(child.inner.clone())).to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn sin(child: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Unary(UnaryNode::Sin(Box::new(child.inner.clone()))),
+            inner: UnaryNode::Sin(Box::new(child.inner.clone())).to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn cos(child: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Unary(UnaryNode::Cos(Box::new(child.inner.clone()))),
+            inner: UnaryNode::Cos(Box::new(child.inner.clone())).to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn exp(child: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Unary(UnaryNode::Exp(Box::new(child.inner.clone()))),
+            inner: UnaryNode::Exp(Box::new(child.inner.clone())).to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn ln(child: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Unary(UnaryNode::Ln(Box::new(child.inner.clone()))),
+            inner: UnaryNode::Ln(Box::new(child.inner.clone())).to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
@@ -144,83 +224,131 @@ impl PyExpr {
     #[staticmethod]
     pub fn add(left: &PyExpr, right: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Binary(BinaryNode::Add(
-                Box::new(left.inner.clone()),
-                Box::new(right.inner.clone()),
-            )),
+            inner: BinaryNode::Add(Box::new(left.inner.clone()), Box::new(right.inner.clone()))
+                .to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn subtract(left: &PyExpr, right: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Binary(BinaryNode::Subtract(
+            inner: BinaryNode::Subtract(
                 Box::new(left.inner.clone()),
                 Box::new(right.inner.clone()),
-            )),
+            )
+            .to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn multiply(left: &PyExpr, right: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Binary(BinaryNode::Multiply(
+            inner: BinaryNode::Multiply(
                 Box::new(left.inner.clone()),
                 Box::new(right.inner.clone()),
-            )),
+            )
+            .to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn frac(numerator: &PyExpr, denominator: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Binary(BinaryNode::Frac(
+            inner: BinaryNode::Frac(
                 Box::new(numerator.inner.clone()),
                 Box::new(denominator.inner.clone()),
-            )),
+            )
+            .to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn pow(base: &PyExpr, exponent: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Binary(BinaryNode::Pow(
-                Box::new(base.inner.clone()),
-                Box::new(exponent.inner.clone()),
-            )),
+            inner: BinaryNode::Pow(Box::new(base.inner.clone()), Box::new(exponent.inner.clone()))
+                .to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     #[staticmethod]
     pub fn log(base: &PyExpr, argument: &PyExpr) -> Self {
         PyExpr {
-            inner: Expr::Binary(BinaryNode::Log(
-                Box::new(base.inner.clone()),
-                Box::new(argument.inner.clone()),
-            )),
+            inner: BinaryNode::Log(Box::new(base.inner.clone()), Box::new(argument.inner.clone()))
+                .to_expr(true),
             compiled_fn: None,
+            compiled_fn_vec: None,
         }
     }
 
     // Example of how to inspect the stored enum from Python.
     pub fn is_leaf(&self) -> bool {
-        matches!(self.inner, Expr::Leaf(_))
+        matches!(self.inner, Expr::Initialized(InitializedExpr::Leaf(_)))
     }
 
     // Optionally get details, e.g. which leaf node type?
     pub fn as_constant(&self) -> Option<f64> {
         match &self.inner {
-            Expr::Leaf(LeafNode::Constant(val)) => Some(*val),
+            Expr::Initialized(InitializedExpr::Leaf(InitializedLeaf::Constant(val))) => Some(*val),
             _ => None,
         }
     }
 }
 
+/// # Ensure compiled nd
+/// Jit-compile `inner`'s scalar kernel into `cache`, if it hasn't been compiled yet, and return a
+/// reference to it. Takes the cache field directly (rather than being a `&mut self` method) so
+/// callers can hold the returned reference alongside other, disjoint `&mut` borrows of `self` -
+/// e.g. `evaluate_vec` also needs to populate `compiled_fn_vec` while this reference is still
+/// alive. `compile_nd_cse` is used over the plain `compile_nd` so constant folding and
+/// common-subexpression elimination benefit both `evaluate` and `evaluate_vec`.
+fn ensure_compiled_nd<'a>(
+    inner: &Expr,
+    cache: &'a mut Option<CompiledExpressionND>,
+) -> &'a CompiledExpressionND {
+    if cache.is_none() {
+        *cache = Some(inner.compile_nd_cse().unwrap());
+    }
+    cache.as_ref().unwrap()
+}
+
+/// # Ensure compiled nd vec
+/// Like [`ensure_compiled_nd`], but for the vectorized kernel used when `evaluate_vec` is called
+/// with `vectorize=true`.
+fn ensure_compiled_nd_vec<'a>(
+    inner: &Expr,
+    cache: &'a mut Option<CompiledExpressionNDVec>,
+) -> &'a CompiledExpressionNDVec {
+    if cache.is_none() {
+        *cache = Some(inner.compile_nd_vec(VEC_LANE_WIDTH).unwrap());
+    }
+    cache.as_ref().unwrap()
+}
+
+/// # Evaluate row
+/// Call the scalar kernel for one row, respecting the row's actual memory layout. A
+/// non-contiguous row, such as one read out of a transposed array or a broadcast view produced
+/// by `np.broadcast_to`, can't be handed to the kernel as a raw pointer, since the kernel assumes
+/// unit-stride `f64`s; gather those into a small contiguous scratch buffer instead.
+fn evaluate_row(f: &CompiledExpressionND, row: ArrayView1<f64>, cols: usize) -> f64 {
+    match row.as_slice() {
+        Some(slice) => f.call(slice.as_ptr(), cols),
+        None => {
+            let staged: Vec<f64> = row.iter().copied().collect();
+            f.call(staged.as_ptr(), cols)
+        }
+    }
+}
+
 /// Formats the sum of two numbers as string.
 #[pyfunction]
 fn sum_as_string(a: usize, b: usize) -> PyResult<String> {
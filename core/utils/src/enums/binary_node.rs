@@ -131,6 +131,7 @@ impl Expression for BinaryNode {
 #[cfg(test)]
 mod tests {
     use crate::enums::leaf_node::LeafNode;
+    use crate::enums::scalar_type::ScalarType;
 
     use super::*;
 
@@ -192,7 +193,7 @@ mod tests {
         );
         let func_2 = BinaryNode::Multiply(
             Box::new(Expr::Leaf(LeafNode::Variable(0))),
-            Box::new(Expr::Leaf(LeafNode::Constant(2.))),
+            Box::new(Expr::Leaf(LeafNode::Constant(ScalarType::F64(2.)))),
         );
 
         let values_1 = vec![3.0, 4.0];
@@ -1,3 +1,4 @@
+use crate::enums::scalar_type::ScalarType;
 use crate::traits::expression::Expression;
 use cranelift_codegen::ir::{types, MemFlags};
 use cranelift_codegen::ir::{InstBuilder, Value};
@@ -9,21 +10,23 @@ use cranelift_frontend::FunctionBuilder;
 ///
 /// Being a leaf node, this node has no children.
 pub enum LeafNode {
-    Constant(f64),   // Leaf node: a constant value.
+    // Leaf node: a constant value, stored as a `ScalarType` rather than a bare `f64` so constants
+    // compare, order, and hash the same NaN-safe way `ScalarType` already guarantees elsewhere.
+    Constant(ScalarType),
     Variable(usize), // The usize is the index of the variable in the input vector.
 }
 
 impl Expression for LeafNode {
     fn evaluate(&self, variables: &Vec<f64>) -> f64 {
         match self {
-            LeafNode::Constant(value) => *value,
+            LeafNode::Constant(value) => value.widen(),
             LeafNode::Variable(idx) => variables[*idx],
         }
     }
 
     fn build_jit(&self, builder: &mut FunctionBuilder, parameters: &[Value]) -> Value {
         match self {
-            LeafNode::Constant(value) => builder.ins().f64const(*value),
+            LeafNode::Constant(value) => builder.ins().f64const(value.widen()),
             LeafNode::Variable(idx) => {
                 let args_ptr = parameters[0]; // *const f64
 
@@ -66,7 +69,7 @@ mod tests {
 
     #[test]
     fn test_expression_constant() {
-        let f = LeafNode::Constant(2.0).compile().unwrap();
+        let f = LeafNode::Constant(ScalarType::F64(2.0)).compile().unwrap();
         let values = vec![];
         assert_eq!(f(values.as_ptr(), values.len()), 2.0);
     }
@@ -87,7 +90,7 @@ mod tests {
         let variables = vec![1.0, 2.0, 3.0];
 
         // f(x) = 2
-        let expr = LeafNode::Constant(2.0);
+        let expr = LeafNode::Constant(ScalarType::F64(2.0));
         assert_eq!(expr.evaluate(&variables), 2.0);
     }
 }
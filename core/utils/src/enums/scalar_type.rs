@@ -1,7 +1,17 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
+use crate::errors::scalar_type_error::ScalarTypeError;
+
 /// An enum to represent a scalar value.
-#[derive(Clone, Copy, Debug, PartialEq)]
+///
+/// `PartialEq`/`Eq`/`Hash`/`Ord` are all hand-implemented in terms of [`ScalarType::widen`] rather
+/// than derived, following the approach fj-math uses for its `Scalar` wrapper: values compare and
+/// hash by their widened `f64` bit pattern, so `F32` and `F64` values that represent the same
+/// number are equal, and (unlike the bare floats) every value - including NaN, should one sneak in
+/// through the unchecked [`From`] impls below - has a well-defined, total order and a stable hash.
+#[derive(Clone, Copy, Debug)]
 pub enum ScalarType {
     F64(f64),
     F32(f32),
@@ -64,6 +74,113 @@ impl Neg for ScalarType {
     }
 }
 
+impl ScalarType {
+    /// Zero, as an `f64`.
+    pub const ZERO_F64: ScalarType = ScalarType::F64(0.0);
+    /// One, as an `f64`.
+    pub const ONE_F64: ScalarType = ScalarType::F64(1.0);
+    /// Pi, as an `f64`.
+    pub const PI_F64: ScalarType = ScalarType::F64(std::f64::consts::PI);
+    /// Tau (2*pi), as an `f64`.
+    pub const TAU_F64: ScalarType = ScalarType::F64(std::f64::consts::TAU);
+    /// Euler's number, as an `f64`.
+    pub const E_F64: ScalarType = ScalarType::F64(std::f64::consts::E);
+
+    /// Zero, as an `f32`.
+    pub const ZERO_F32: ScalarType = ScalarType::F32(0.0);
+    /// One, as an `f32`.
+    pub const ONE_F32: ScalarType = ScalarType::F32(1.0);
+    /// Pi, as an `f32`.
+    pub const PI_F32: ScalarType = ScalarType::F32(std::f32::consts::PI);
+    /// Tau (2*pi), as an `f32`.
+    pub const TAU_F32: ScalarType = ScalarType::F32(std::f32::consts::TAU);
+    /// Euler's number, as an `f32`.
+    pub const E_F32: ScalarType = ScalarType::F32(std::f32::consts::E);
+
+    /// # From f64
+    /// Checked constructor: builds a `ScalarType::F64`, rejecting NaN so every `ScalarType` that
+    /// goes through this path (rather than the unchecked `From<f64>` impl) is guaranteed orderable
+    /// and hashable in the way a caller would expect.
+    pub fn from_f64(value: f64) -> Result<Self, ScalarTypeError> {
+        if value.is_nan() {
+            return Err(ScalarTypeError::new_nan());
+        }
+
+        Ok(ScalarType::F64(value))
+    }
+
+    /// # From f32
+    /// Checked constructor: builds a `ScalarType::F32`, rejecting NaN. See
+    /// [`ScalarType::from_f64`].
+    pub fn from_f32(value: f32) -> Result<Self, ScalarTypeError> {
+        if value.is_nan() {
+            return Err(ScalarTypeError::new_nan());
+        }
+
+        Ok(ScalarType::F32(value))
+    }
+
+    /// # Widen
+    /// Widen to `f64`, the same promotion the arithmetic ops above apply when mixing an `F32` with
+    /// an `F64`. The basis for this type's `PartialEq`/`Eq`/`Ord`/`Hash` impls, and `pub(crate)` so
+    /// [`crate::enums::leaf_node::LeafNode::Constant`] can recover a plain `f64` to hand to
+    /// `evaluate`/`build_jit`, which don't otherwise need to know about `ScalarType`.
+    pub(crate) fn widen(self) -> f64 {
+        match self {
+            ScalarType::F64(value) => value,
+            ScalarType::F32(value) => f64::from(value),
+        }
+    }
+}
+
+impl TryFrom<f64> for ScalarType {
+    type Error = ScalarTypeError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        ScalarType::from_f64(value)
+    }
+}
+
+impl TryFrom<f32> for ScalarType {
+    type Error = ScalarTypeError;
+
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        ScalarType::from_f32(value)
+    }
+}
+
+/// Total ordering over the widened `f64` representation, via [`f64::total_cmp`] so NaN (should
+/// one reach this type through an unchecked constructor) still orders and compares consistently
+/// instead of comparing unequal to everything, including itself.
+impl Ord for ScalarType {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.widen().total_cmp(&other.widen())
+    }
+}
+
+impl PartialOrd for ScalarType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Defined in terms of `Ord` rather than derived, so equality agrees with the total order above
+/// (and therefore with `Hash`) instead of falling back to `f64`'s `PartialEq`, under which NaN is
+/// unequal to itself.
+impl PartialEq for ScalarType {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScalarType {}
+
+impl Hash for ScalarType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.widen().to_bits().hash(state);
+    }
+}
+
 /// Add the powf function to the ScalarType enum.
 impl ScalarType {
     pub fn powf(self, exponent: ScalarType) -> Self {
@@ -309,4 +426,73 @@ mod tests {
         assert_eq!(f64_value, ScalarType::F64(1.0));
         assert_eq!(f32_value, ScalarType::F32(1.0));
     }
+
+    #[test]
+    fn test_from_f64_rejects_nan() {
+        assert!(ScalarType::from_f64(f64::NAN).is_err());
+        assert_eq!(ScalarType::from_f64(1.0).unwrap(), ScalarType::F64(1.0));
+    }
+
+    #[test]
+    fn test_from_f32_rejects_nan() {
+        assert!(ScalarType::from_f32(f32::NAN).is_err());
+        assert_eq!(ScalarType::from_f32(1.0).unwrap(), ScalarType::F32(1.0));
+    }
+
+    #[test]
+    fn test_try_from_rejects_nan() {
+        assert!(ScalarType::try_from(f64::NAN).is_err());
+        assert!(ScalarType::try_from(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn test_eq_widens_across_variants() {
+        // F32 and F64 values that represent the same number should compare equal.
+        assert_eq!(ScalarType::F32(1.0), ScalarType::F64(1.0));
+        assert_ne!(ScalarType::F32(1.0), ScalarType::F64(2.0));
+    }
+
+    #[test]
+    fn test_ord_sorts_by_value() {
+        let mut values = vec![
+            ScalarType::F64(3.0),
+            ScalarType::F32(1.0),
+            ScalarType::F64(2.0),
+        ];
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                ScalarType::F32(1.0),
+                ScalarType::F64(2.0),
+                ScalarType::F64(3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hash_agrees_with_eq() {
+        use std::collections::HashSet;
+
+        let mut seen = HashSet::new();
+        seen.insert(ScalarType::F64(1.0));
+
+        // A different-width representation of the same value should hit the same hash bucket.
+        assert!(seen.contains(&ScalarType::F32(1.0)));
+    }
+
+    #[test]
+    fn test_named_constants() {
+        assert_eq!(ScalarType::ZERO_F64, ScalarType::F64(0.0));
+        assert_eq!(ScalarType::ONE_F64, ScalarType::F64(1.0));
+        assert_eq!(ScalarType::PI_F64, ScalarType::F64(std::f64::consts::PI));
+        assert_eq!(ScalarType::TAU_F64, ScalarType::F64(std::f64::consts::TAU));
+        assert_eq!(ScalarType::E_F64, ScalarType::F64(std::f64::consts::E));
+
+        assert_eq!(ScalarType::ZERO_F32, ScalarType::F32(0.0));
+        assert_eq!(ScalarType::ONE_F32, ScalarType::F32(1.0));
+        assert_eq!(ScalarType::PI_F32, ScalarType::F32(std::f32::consts::PI));
+        assert_eq!(ScalarType::TAU_F32, ScalarType::F32(std::f32::consts::TAU));
+        assert_eq!(ScalarType::E_F32, ScalarType::F32(std::f32::consts::E));
+    }
 }
@@ -0,0 +1,27 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub struct ScalarTypeError {
+    details: String,
+}
+
+impl ScalarTypeError {
+    /// Create a new ScalarTypeError for a value that isn't a valid scalar (currently, only NaN).
+    pub(crate) fn new_nan() -> ScalarTypeError {
+        ScalarTypeError {
+            details: "NaN is not a valid ScalarType value".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ScalarTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ScalarTypeError: {}", self.details)
+    }
+}
+
+impl std::error::Error for ScalarTypeError {
+    fn description(&self) -> &str {
+        &self.details
+    }
+}